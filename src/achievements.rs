@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::gameplay::*;
+use crate::highscores::today;
+use crate::replay::ReplayPlayback;
+use crate::stats::MatchStats;
+use crate::storage::{load_ron, save_ron};
+use crate::GameState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    BombsSpawned,
+    GemsCollected,
+    ManaGained,
+    PlayerHits,
+    EnemiesKilled,
+}
+
+impl Counter {
+    pub fn get(self, stats: &MatchStats) -> u32 {
+        match self {
+            Counter::BombsSpawned => stats.bombs_spawned,
+            Counter::GemsCollected => stats.gems_collected,
+            Counter::ManaGained => stats.mana_gained,
+            Counter::PlayerHits => stats.player_hits,
+            Counter::EnemiesKilled => stats.enemies_killed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Requirement {
+    // only known once the match is over
+    Win,
+    // only known once the match is over
+    AtMost(Counter, u32),
+    AtLeast(Counter, u32),
+    Survive(f32),
+}
+
+// all requirements have to be met in the same match
+#[derive(Debug, PartialEq)]
+pub struct Achievement {
+    // saved to disk, never change it once released
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub requirements: &'static [Requirement],
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_win",
+        name: "Slime Slayer",
+        description: "Win a match",
+        requirements: &[Requirement::Win],
+    },
+    Achievement {
+        id: "no_gems",
+        name: "Ascetic",
+        description: "Win without collecting a gem",
+        requirements: &[Requirement::Win, Requirement::AtMost(Counter::GemsCollected, 0)],
+    },
+    Achievement {
+        id: "few_bombs",
+        name: "Sharpshooter",
+        description: "Win with fewer than 10 bombs",
+        requirements: &[Requirement::Win, Requirement::AtMost(Counter::BombsSpawned, 9)],
+    },
+    Achievement {
+        id: "untouchable",
+        name: "Untouchable",
+        description: "Win without getting hit",
+        requirements: &[Requirement::Win, Requirement::AtMost(Counter::PlayerHits, 0)],
+    },
+    Achievement {
+        id: "survive_5_minutes",
+        name: "Survivor",
+        description: "Survive for 5 minutes",
+        requirements: &[Requirement::Survive(300.0)],
+    },
+    Achievement {
+        id: "mana_500",
+        name: "Hoarder",
+        description: "Gain 500 mana from gems in one match",
+        requirements: &[Requirement::AtLeast(Counter::ManaGained, 500)],
+    },
+    Achievement {
+        id: "bombs_100",
+        name: "Demolition",
+        description: "Summon 100 bombs in one match",
+        requirements: &[Requirement::AtLeast(Counter::BombsSpawned, 100)],
+    },
+    Achievement {
+        id: "kills_25",
+        name: "Exterminator",
+        description: "Kill 25 slimes in one match",
+        requirements: &[Requirement::AtLeast(Counter::EnemiesKilled, 25)],
+    },
+];
+
+impl Achievement {
+    pub fn by_id(id: &str) -> Option<&'static Achievement> {
+        ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)
+    }
+
+    // requirements about the whole match are only met once the result is decided
+    pub fn is_met(&self, stats: &MatchStats, result: GameResult) -> bool {
+        let finished = result != GameResult::None;
+        self.requirements.iter().all(|requirement| match *requirement {
+            Requirement::Win => result == GameResult::Win,
+            Requirement::AtMost(counter, value) => finished && counter.get(stats) <= value,
+            Requirement::AtLeast(counter, value) => counter.get(stats) >= value,
+            Requirement::Survive(seconds) => stats.time_survived >= seconds,
+        })
+    }
+}
+
+// date is YYYY-MM-DD, UTC
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnlockedAchievement {
+    pub id: String,
+    pub date: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct UnlockedAchievements {
+    pub unlocked: Vec<UnlockedAchievement>,
+}
+
+impl UnlockedAchievements {
+    pub fn get(&self, id: &str) -> Option<&UnlockedAchievement> {
+        self.unlocked.iter().find(|unlocked| unlocked.id == id)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    // drops duplicates and ids that no longer exist
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        let saved: Self = ron::from_str(text)?;
+        let mut achievements = Self::default();
+        for unlocked in saved.unlocked {
+            if Achievement::by_id(&unlocked.id).is_some() && achievements.get(&unlocked.id).is_none() {
+                achievements.unlocked.push(unlocked);
+            }
+        }
+        Ok(achievements)
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct AchievementStore {
+    pub path: Option<String>,
+}
+
+impl AchievementStore {
+    pub fn load(&self) -> UnlockedAchievements {
+        load_ron(self.path.as_deref(), "achievements", UnlockedAchievements::from_ron)
+    }
+
+    pub fn save(&self, achievements: &UnlockedAchievements) {
+        save_ron(self.path.as_deref(), "achievements", || achievements.to_ron())
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AchievementUnlocked(pub &'static Achievement);
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // resources
+            .init_resource::<AchievementStore>()
+            .init_resource::<UnlockedAchievements>()
+
+            // events
+            .add_event::<AchievementUnlocked>()
+
+            // startup systems
+            .add_systems(Startup, load_achievements)
+
+            // fixed update systems, after the stats of the tick are recorded, and still running
+            // on the tick that decides the match
+            .add_systems(FixedUpdate, unlock_achievements
+                .after(tick_match_stats)
+                .run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn load_achievements(mut commands: Commands, store: Res<AchievementStore>) {
+    commands.insert_resource(store.load());
+}
+
+fn unlock_achievements(
+    game_status: Res<GameStatus>,
+    store: Res<AchievementStore>,
+    mut achievements: ResMut<UnlockedAchievements>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut ew_unlocked: EventWriter<AchievementUnlocked>,
+) {
+    // a replay is a match that has already been played
+    if playback.is_some() {
+        return;
+    }
+
+    let mut changed = false;
+    for achievement in ACHIEVEMENTS.iter() {
+        if achievements.get(achievement.id).is_some()
+            || !achievement.is_met(&game_status.stats, game_status.result)
+        {
+            continue;
+        }
+
+        info!("Achievement unlocked: {}", achievement.name);
+        achievements.unlocked.push(UnlockedAchievement {
+            id: achievement.id.to_string(),
+            date: today(),
+        });
+        ew_unlocked.send(AchievementUnlocked(achievement));
+        changed = true;
+    }
+
+    if changed {
+        store.save(&achievements);
+    }
+}
@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use crate::gameplay::{Fuse, Health, InGameScreen};
+use crate::ron_asset::*;
+
+// pixels per second above which an entity plays its Move animation
+const MOVING_SPEED: f32 = 15.0;
+
+const FUSE_SPEEDUP: f32 = 4.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationKind {
+    Idle,
+    Move,
+    Hurt,
+    Death,
+    Fuse,
+}
+
+// keyframes are spread evenly over the duration; the sprites only have one frame, so scale and
+// tint are all an animation changes
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AnimationTag {
+    pub duration: f32,
+    // tags that don't loop hold their last keyframe
+    pub repeat: bool,
+    #[serde(default)]
+    pub scale: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub tint: Vec<(f32, f32, f32, f32)>,
+}
+
+impl AnimationTag {
+    fn progress(&self, elapsed: f32) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else if self.repeat {
+            (elapsed / self.duration).fract()
+        } else {
+            (elapsed / self.duration).min(1.0)
+        }
+    }
+
+    pub fn finished(&self, elapsed: f32) -> bool {
+        !self.repeat && elapsed >= self.duration
+    }
+
+    pub fn scale(&self, elapsed: f32) -> Vec2 {
+        sample(&self.scale, self.progress(elapsed), |(x0, y0), (x1, y1), t| {
+            (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+        })
+        .map_or(Vec2::ONE, |(x, y)| Vec2::new(x, y))
+    }
+
+    pub fn tint(&self, elapsed: f32) -> Option<Color> {
+        sample(&self.tint, self.progress(elapsed), |(r0, g0, b0, a0), (r1, g1, b1, a1), t| {
+            Vec4::new(r0, g0, b0, a0).lerp(Vec4::new(r1, g1, b1, a1), t).into()
+        })
+        .map(|(r, g, b, a)| Color::rgba(r, g, b, a))
+    }
+}
+
+pub(crate) fn sample<T: Copy>(keys: &[T], progress: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    match keys.len() {
+        0 => None,
+        1 => Some(keys[0]),
+        len => {
+            let position = progress.clamp(0.0, 1.0) * (len - 1) as f32;
+            let index = (position as usize).min(len - 2);
+            Some(lerp(keys[index], keys[index + 1], position - index as f32))
+        }
+    }
+}
+
+// loaded from the *.anim.ron file next to the sprite's PNG
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct AnimationSet {
+    // before any squash or stretch
+    pub size: (f32, f32),
+    pub tags: HashMap<AnimationKind, AnimationTag>,
+}
+
+impl AnimationSet {
+    pub fn from_ron(text: &str) -> Result<Self, RonAssetError> {
+        parse_ron(text)
+    }
+
+    // falls back to Idle when the set has no such tag
+    pub fn tag(&self, kind: AnimationKind) -> Option<&AnimationTag> {
+        self.tags.get(&kind).or_else(|| self.tags.get(&AnimationKind::Idle))
+    }
+}
+
+impl Validate for AnimationSet {}
+
+// which tag plays is picked from the entity's state every frame
+#[derive(Component, Debug, Clone)]
+pub struct SpriteAnimation {
+    pub animations: Handle<AnimationSet>,
+    pub kind: AnimationKind,
+    pub elapsed: f32,
+    pub speed: f32,
+    // the set's size when unset
+    pub base_size: Option<Vec2>,
+    last_position: Option<Vec2>,
+    tinted: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(animations: Handle<AnimationSet>) -> Self {
+        Self {
+            animations,
+            kind: AnimationKind::Idle,
+            elapsed: 0.0,
+            speed: 1.0,
+            base_size: None,
+            last_position: None,
+            tinted: false,
+        }
+    }
+
+    pub fn with_base_size(mut self, size: Vec2) -> Self {
+        self.base_size = Some(size);
+        self
+    }
+
+    // starts the tag over unless it is already playing
+    pub fn play(&mut self, kind: AnimationKind) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.elapsed = 0.0;
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Corpse;
+
+pub fn spawn_corpse(
+    commands: &mut Commands,
+    animation: &SpriteAnimation,
+    texture: Handle<Image>,
+    sprite: &Sprite,
+    transform: Transform,
+) {
+    let mut corpse = animation.clone();
+    corpse.play(AnimationKind::Death);
+    corpse.speed = 1.0;
+
+    commands.spawn((
+        SpriteBundle {
+            texture,
+            sprite: sprite.clone(),
+            transform,
+            ..default()
+        },
+        corpse,
+        Corpse,
+        Name::new("Corpse"),
+        InGameScreen,
+    ));
+}
+
+// must be added before the loading state that loads the sets
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // assets
+            .init_asset::<AnimationSet>()
+            .register_asset_loader(RonAssetLoader::<AnimationSet>::new(&["anim.ron"]))
+
+            // update systems
+            .add_systems(Update, (
+                select_animations,
+                advance_animations,
+                remove_finished_corpses,
+            ).chain());
+    }
+}
+
+fn select_animations(
+    time: Res<Time>,
+    sets: Res<Assets<AnimationSet>>,
+    mut animation_query: Query<(
+        &mut SpriteAnimation,
+        &Transform,
+        Option<Ref<Health>>,
+        Option<&Fuse>,
+    )>,
+) {
+    for (mut animation, transform, health, fuse) in animation_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let speed = match animation.last_position {
+            Some(last) if time.delta_seconds() > 0.0 => {
+                last.distance(position) / time.delta_seconds()
+            }
+            _ => 0.0,
+        };
+        animation.last_position = Some(position);
+
+        let playing = sets
+            .get(&animation.animations)
+            .and_then(|set| set.tag(animation.kind))
+            .is_some_and(|tag| !tag.finished(animation.elapsed));
+
+        match animation.kind {
+            // nothing comes after death
+            AnimationKind::Death => continue,
+            _ if health.is_some_and(|health| health.is_changed() && !health.is_added()) => {
+                animation.kind = AnimationKind::Hurt;
+                animation.elapsed = 0.0;
+            }
+            AnimationKind::Hurt if playing => {}
+            _ => {
+                if let Some(fuse) = fuse {
+                    animation.play(AnimationKind::Fuse);
+                    animation.speed = 1.0 + FUSE_SPEEDUP * fuse.timer.fraction();
+                } else if speed > MOVING_SPEED {
+                    animation.play(AnimationKind::Move);
+                } else {
+                    animation.play(AnimationKind::Idle);
+                }
+            }
+        }
+    }
+}
+
+pub fn advance_animations(
+    time: Res<Time>,
+    sets: Res<Assets<AnimationSet>>,
+    mut animation_query: Query<(&mut SpriteAnimation, &mut Sprite)>,
+) {
+    for (mut animation, mut sprite) in animation_query.iter_mut() {
+        let Some(set) = sets.get(&animation.animations) else {
+            continue;
+        };
+        let Some(tag) = set.tag(animation.kind) else {
+            continue;
+        };
+
+        animation.elapsed += time.delta_seconds() * animation.speed;
+        let elapsed = animation.elapsed;
+
+        let (width, height) = set.size;
+        let base_size = animation.base_size.unwrap_or(Vec2::new(width, height));
+        sprite.custom_size = Some(base_size * tag.scale(elapsed));
+
+        // only touch the colour while tinting, so other systems can flash the sprite meanwhile
+        match tag.tint(elapsed) {
+            Some(tint) => {
+                sprite.color = tint;
+                animation.tinted = true;
+            }
+            None if animation.tinted => {
+                sprite.color = Color::WHITE;
+                animation.tinted = false;
+            }
+            None => {}
+        }
+    }
+}
+
+fn remove_finished_corpses(
+    mut commands: Commands,
+    sets: Res<Assets<AnimationSet>>,
+    corpse_query: Query<(Entity, &SpriteAnimation), With<Corpse>>,
+) {
+    for (corpse, animation) in corpse_query.iter() {
+        let finished = match sets.get(&animation.animations) {
+            Some(set) => set
+                .tag(animation.kind)
+                .is_none_or(|tag| tag.finished(animation.elapsed)),
+            None => true,
+        };
+        if finished {
+            commands.entity(corpse).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// --<name> <value> or --<name>=<value> on the command line
+#[cfg(not(target_family = "wasm"))]
+pub fn launch_arg(name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix(&flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        } else if arg == flag {
+            return args.next();
+        }
+    }
+    None
+}
+
+// the ?<name>=<value> query parameter of the page URL
+#[cfg(target_family = "wasm")]
+pub fn launch_arg(name: &str) -> Option<String> {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+        .and_then(|params| params.get(name))
+}
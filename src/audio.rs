@@ -1,35 +1,245 @@
-use bevy::asset::{AssetServer, Handle};
-use bevy::log::info;
-use bevy::prelude::{Commands, Res, Resource};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy_kira_audio::prelude::*;
+use rand::Rng;
+use std::time::Duration;
 use crate::loading::AudioAssets;
+use crate::music::MusicDirector;
+use crate::settings::Settings;
+use crate::{CameraMarker, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+pub const MUSIC_VOLUME: f64 = 0.3;
+
+pub const EFFECTS_VOLUME: f64 = 0.5;
+
+const DUCKED_MUSIC: f64 = 0.35;
+
+const DUCK_DURATION: f32 = 1.5;
+
+// 0.5 pans a sound at the edge of the screen all the way to one ear
+const MAX_PAN: f64 = 0.35;
+
+const FAR_VOLUME: f64 = 0.5;
 
 #[derive(Resource)]
-pub struct AudioResource(pub Handle<AudioInstance>);
+pub struct MusicChannel;
+
+#[derive(Resource)]
+pub struct EffectsChannel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SfxKind {
+    SpawnMinion,
+    OutOfMana,
+    Explosion,
+    PlayerDie,
+    EnemyDie,
+    ManaGem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SfxSpec {
+    pub volume: f64,
+    // e.g. 0.1 for any playback rate from 0.9 to 1.1
+    pub pitch_variation: f64,
+    // further requests are dropped until one of them ends
+    pub max_instances: usize,
+    pub ducks_music: bool,
+}
+
+impl SfxKind {
+    pub fn spec(self) -> SfxSpec {
+        let (volume, pitch_variation, max_instances, ducks_music) = match self {
+            SfxKind::SpawnMinion => (1.0, 0.05, 2, false),
+            SfxKind::OutOfMana => (1.0, 0.0, 1, false),
+            SfxKind::Explosion => (1.0, 0.08, 6, false),
+            SfxKind::PlayerDie => (1.0, 0.0, 1, true),
+            SfxKind::EnemyDie => (1.0, 0.05, 4, true),
+            SfxKind::ManaGem => (0.8, 0.1, 4, false),
+        };
+        SfxSpec {
+            volume,
+            pitch_variation,
+            max_instances,
+            ducks_music,
+        }
+    }
+
+    fn source(self, audio_assets: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            SfxKind::SpawnMinion => audio_assets.spawn_minion.clone(),
+            SfxKind::OutOfMana => audio_assets.oom.clone(),
+            SfxKind::Explosion => audio_assets.minion_die.clone(),
+            SfxKind::PlayerDie => audio_assets.player_die.clone(),
+            SfxKind::EnemyDie => audio_assets.enemy_die.clone(),
+            SfxKind::ManaGem => audio_assets.mana_gem.clone(),
+        }
+    }
+}
+
+// sounds with a position are panned and attenuated relative to the camera
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PlaySfx {
+    pub kind: SfxKind,
+    pub position: Option<Vec2>,
+}
+
+impl PlaySfx {
+    pub fn new(kind: SfxKind) -> Self {
+        Self { kind, position: None }
+    }
+
+    pub fn at(kind: SfxKind, position: Vec2) -> Self {
+        Self {
+            kind,
+            position: Some(position),
+        }
+    }
+}
+
+// returns the volume factor and the panning (0 left, 0.5 centre, 1 right)
+pub fn spatial_mix(offset: Vec2) -> (f64, f64) {
+    let half_extents = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT) / 2.0;
+    let side = (offset.x / half_extents.x).clamp(-1.0, 1.0) as f64;
+    let distance = (offset.length() / half_extents.length()).min(1.0) as f64;
+    (1.0 - (1.0 - FAR_VOLUME) * distance, 0.5 + MAX_PAN * side)
+}
 
-// #[derive(Resource)]
-// pub struct MusicChannel;
+#[derive(Resource, Debug, Default)]
+pub struct SfxInstances(pub HashMap<SfxKind, Vec<Handle<AudioInstance>>>);
 
-// #[derive(Resource)]
-// pub struct EffectsChannel;
+impl SfxInstances {
+    pub fn count(&self, kind: SfxKind) -> usize {
+        self.0.get(&kind).map_or(0, Vec::len)
+    }
+}
+
+#[derive(Event, Debug, Default, Clone, Copy)]
+pub struct DuckMusic;
+
+#[derive(Resource, Debug, Default)]
+pub struct MusicDucking(pub Option<Timer>);
+
+// the channels themselves are added alongside the bevy_kira_audio plugin
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // events
+            .add_event::<PlaySfx>()
+            .add_event::<DuckMusic>()
+
+            // resources
+            .init_resource::<MusicDucking>()
+            .init_resource::<SfxInstances>()
 
-pub fn play_bgm(
-    mut commands: Commands,
+            // update systems
+            .add_systems(Update, (
+                play_sfx,
+                duck_music,
+                apply_music_volume,
+            ).chain());
+    }
+}
+
+// level is the share of the volume the music director wants for the current track and intensity
+pub fn music_volume(settings: &Settings, ducking: &MusicDucking, level: f64) -> f64 {
+    let duck = if ducking.0.is_some() { DUCKED_MUSIC } else { 1.0 };
+    MUSIC_VOLUME * settings.music_volume * duck * level
+}
+
+fn play_sfx(
+    mut er_play_sfx: EventReader<PlaySfx>,
+    mut instances: ResMut<SfxInstances>,
+    settings: Res<Settings>,
     audio_assets: Res<AudioAssets>,
-    // music_channel: Res<AudioChannel<MusicChannel>>,
-    audio: Res<Audio>,
+    effects_channel: Res<AudioChannel<EffectsChannel>>,
+    camera_query: Query<&GlobalTransform, With<CameraMarker>>,
+    mut ew_duck_music: EventWriter<DuckMusic>,
+) {
+    // forget the instances that have ended
+    for handles in instances.0.values_mut() {
+        handles.retain(|handle| effects_channel.state(handle) != PlaybackState::Stopped);
+    }
+
+    // pitch variation is cosmetic, so it must not draw from the gameplay rng
+    let mut rng = rand::thread_rng();
+
+    let listener = camera_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation().truncate());
+
+    for PlaySfx { kind, position } in er_play_sfx.read() {
+        let spec = kind.spec();
+        if instances.count(*kind) >= spec.max_instances {
+            trace!("Dropping {:?}, {} already playing", kind, spec.max_instances);
+            continue;
+        }
+
+        let playback_rate = if spec.pitch_variation > 0.0 {
+            1.0 + rng.gen_range(-spec.pitch_variation..=spec.pitch_variation)
+        } else {
+            1.0
+        };
+        // effects are short and the camera barely moves, so they are placed once when started
+        let (attenuation, panning) = match position {
+            Some(position) => spatial_mix(*position - listener),
+            None => (1.0, 0.5),
+        };
+        let handle = effects_channel
+            .play(kind.source(&audio_assets))
+            .with_volume(EFFECTS_VOLUME * settings.effects_volume * spec.volume * attenuation)
+            .with_panning(panning)
+            .with_playback_rate(playback_rate)
+            .handle();
+        instances.0.entry(*kind).or_default().push(handle);
+
+        if spec.ducks_music {
+            ew_duck_music.send(DuckMusic);
+        }
+    }
+}
+
+fn duck_music(
+    time: Res<Time>,
+    mut ducking: ResMut<MusicDucking>,
+    mut er_duck_music: EventReader<DuckMusic>,
 ) {
-    info!("Attempting to play audio!");
-    let handle = audio
-        .play(audio_assets.bgm.clone())
-        .looped()
-        .with_volume(0.3)
-        .handle();
-    info!("Spawned auto resource: {:?}", handle.clone());
-    commands.insert_resource(AudioResource(handle));
-    // music_channel.pause();
-    // music_channel
-    //     .play(audio_assets.bgm.clone())
-    //     .looped()
-    //     .with_volume(0.25);
+    if er_duck_music.read().count() > 0 {
+        ducking.0 = Some(Timer::from_seconds(DUCK_DURATION, TimerMode::Once));
+    } else if ducking.0.as_mut().is_some_and(|timer| timer.tick(time.delta()).finished()) {
+        ducking.0 = None;
+    }
+}
+
+// the only system that sets the volume of the whole music channel; effects get theirs when played
+fn apply_music_volume(
+    settings: Res<Settings>,
+    ducking: Res<MusicDucking>,
+    director: Option<Res<MusicDirector>>,
+    mut applied: Local<Option<f64>>,
+    music_channel: Res<AudioChannel<MusicChannel>>,
+) {
+    let level = director.map_or(1.0, |director| director.level());
+    let volume = music_volume(&settings, &ducking, level);
+    let Some(previous) = *applied else {
+        *applied = Some(volume);
+        music_channel.set_volume(volume);
+        return;
+    };
+    if previous == volume {
+        return;
+    }
+    *applied = Some(volume);
+
+    // duck quickly, come back slowly
+    let fade = if volume < previous {
+        Duration::from_millis(150)
+    } else {
+        Duration::from_secs(1)
+    };
+    music_channel
+        .set_volume(volume)
+        .fade_in(AudioTween::linear(fade));
 }
@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::math::Vector;
+use serde::{Deserialize, Serialize};
+use crate::ron_asset::*;
+use crate::{GameState, HALF_HEIGHT, HALF_WIDTH};
+
+// replaced whenever the file changes on disk (requires the dev feature on desktop)
+#[derive(Asset, Resource, TypePath, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Balance {
+    pub player: PlayerBalance,
+    pub enemy: EnemyBalance,
+    pub dasher: DasherBalance,
+    pub ranged: RangedBalance,
+    pub tank: TankBalance,
+    pub minion: MinionBalance,
+    pub mana_gem: ManaGemBalance,
+    pub waves: WavesBalance,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub health: i32,
+    pub mana: i32,
+    pub spawn_cost: i32,
+    // seconds of invulnerability after taking damage
+    pub invulnerability: f32,
+    pub knockback: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnemyBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub health: i32,
+    pub damage: i32,
+    pub split: SplitBalance,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SplitBalance {
+    // fraction of its maximum health below which a slime splits
+    pub below_health: f32,
+    // how many times the first slime's offspring can split again
+    pub generations: u32,
+    pub radius_scale: f32,
+    pub speed_scale: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DasherBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub health: i32,
+    pub damage: i32,
+    // seconds between two lunges
+    pub cooldown: f32,
+    // seconds spent telegraphing before a lunge
+    pub telegraph: f32,
+    pub lunge: f32,
+    pub lunge_speed: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RangedBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub health: i32,
+    pub damage: i32,
+    pub keep_distance: f32,
+    pub fire_interval: f32,
+    pub projectile_speed: f32,
+    pub projectile_radius: f32,
+    pub projectile_damage: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TankBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub health: i32,
+    pub damage: i32,
+    pub blast_damage_taken: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MinionBalance {
+    pub speed: f32,
+    pub radius: f32,
+    pub damage: i32,
+    pub fuse: f32,
+    pub blast_radius: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManaGemBalance {
+    pub mana: i32,
+    pub radius: f32,
+    pub spawn_interval: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WavesBalance {
+    pub intermission: f32,
+    // fraction of the last wave's health added for every wave past it
+    pub endless_growth: f32,
+    pub list: Vec<WaveBalance>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WaveBalance {
+    pub groups: Vec<WaveGroup>,
+}
+
+// walls are taken in turn
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WaveGroup {
+    pub kind: EnemyKind,
+    pub count: u32,
+    pub health_multiplier: f32,
+    pub walls: Vec<Wall>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyKind {
+    Slime,
+    Dasher,
+    Ranged,
+    Tank,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wall {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl WavesBalance {
+    // past the last wave the last one repeats with one more enemy per group and more health
+    pub fn groups(&self, wave: usize) -> Vec<WaveGroup> {
+        let last = self.list.len() - 1;
+        let Some(extra) = wave.checked_sub(last).filter(|extra| *extra > 0) else {
+            return self.list[wave].groups.clone();
+        };
+
+        self.list[last]
+            .groups
+            .iter()
+            .map(|group| WaveGroup {
+                count: group.count + extra as u32,
+                health_multiplier: group.health_multiplier
+                    * (1.0 + self.endless_growth * extra as f32),
+                ..group.clone()
+            })
+            .collect()
+    }
+
+    pub fn is_last(&self, wave: usize) -> bool {
+        wave + 1 >= self.list.len()
+    }
+}
+
+impl PlayerBalance {
+    pub fn start_position(&self) -> Vector {
+        Vector::new(
+            -HALF_WIDTH + self.radius + 5.0,
+            -HALF_HEIGHT + self.radius + 5.0,
+        )
+    }
+}
+
+impl EnemyBalance {
+    pub fn radius_at(&self, generation: u32) -> f32 {
+        self.radius * self.split.radius_scale.powi(generation as i32)
+    }
+
+    // mass follows the area of the slime, so it shrinks with the square of the radius
+    pub fn mass_at(&self, generation: u32) -> f32 {
+        self.mass * self.split.radius_scale.powi(2 * generation as i32)
+    }
+
+    pub fn speed_at(&self, generation: u32) -> f32 {
+        self.speed * self.split.speed_scale.powi(generation as i32)
+    }
+}
+
+impl Balance {
+    pub fn from_ron(text: &str) -> Result<Self, RonAssetError> {
+        parse_ron(text)
+    }
+
+    // for apps that do not run the asset loader, like the headless tests
+    pub fn bundled() -> Self {
+        Self::from_ron(include_str!("../assets/game.balance.ron"))
+            .expect("the bundled balance file is valid")
+    }
+
+    // FNV-1a over the values, stable across builds, so a replay can tell which balance it was
+    // recorded with
+    pub fn fingerprint(&self) -> u64 {
+        let text = ron::to_string(self).expect("the balance can be serialized");
+        text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+// checks every value, reporting all of the invalid ones at once
+impl Validate for Balance {
+    fn validate(&self) -> Result<(), RonAssetError> {
+        let mut problems = Vec::new();
+
+        let positive = [
+            ("player.speed", self.player.speed),
+            ("player.radius", self.player.radius),
+            ("player.health", self.player.health as f32),
+            ("player.invulnerability", self.player.invulnerability),
+            ("enemy.speed", self.enemy.speed),
+            ("enemy.radius", self.enemy.radius),
+            ("enemy.mass", self.enemy.mass),
+            ("enemy.health", self.enemy.health as f32),
+            ("enemy.split.radius_scale", self.enemy.split.radius_scale),
+            ("enemy.split.speed_scale", self.enemy.split.speed_scale),
+            ("dasher.speed", self.dasher.speed),
+            ("dasher.radius", self.dasher.radius),
+            ("dasher.mass", self.dasher.mass),
+            ("dasher.health", self.dasher.health as f32),
+            ("dasher.cooldown", self.dasher.cooldown),
+            ("dasher.telegraph", self.dasher.telegraph),
+            ("dasher.lunge", self.dasher.lunge),
+            ("dasher.lunge_speed", self.dasher.lunge_speed),
+            ("ranged.speed", self.ranged.speed),
+            ("ranged.radius", self.ranged.radius),
+            ("ranged.mass", self.ranged.mass),
+            ("ranged.health", self.ranged.health as f32),
+            ("ranged.fire_interval", self.ranged.fire_interval),
+            ("ranged.projectile_speed", self.ranged.projectile_speed),
+            ("ranged.projectile_radius", self.ranged.projectile_radius),
+            ("tank.speed", self.tank.speed),
+            ("tank.radius", self.tank.radius),
+            ("tank.mass", self.tank.mass),
+            ("tank.health", self.tank.health as f32),
+            ("minion.speed", self.minion.speed),
+            ("minion.radius", self.minion.radius),
+            ("minion.fuse", self.minion.fuse),
+            ("minion.blast_radius", self.minion.blast_radius),
+            ("mana_gem.radius", self.mana_gem.radius),
+            ("mana_gem.spawn_interval", self.mana_gem.spawn_interval),
+            ("waves.intermission", self.waves.intermission),
+        ];
+        for (name, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                problems.push(format!("{} must be greater than zero (got {})", name, value));
+            }
+        }
+
+        let not_negative = [
+            ("player.mana", self.player.mana as f32),
+            ("player.spawn_cost", self.player.spawn_cost as f32),
+            ("player.knockback", self.player.knockback),
+            ("enemy.damage", self.enemy.damage as f32),
+            ("dasher.damage", self.dasher.damage as f32),
+            ("ranged.damage", self.ranged.damage as f32),
+            ("ranged.keep_distance", self.ranged.keep_distance),
+            ("ranged.projectile_damage", self.ranged.projectile_damage as f32),
+            ("tank.damage", self.tank.damage as f32),
+            ("tank.blast_damage_taken", self.tank.blast_damage_taken),
+            ("minion.damage", self.minion.damage as f32),
+            ("mana_gem.mana", self.mana_gem.mana as f32),
+            ("waves.endless_growth", self.waves.endless_growth),
+        ];
+        for (name, value) in not_negative {
+            if !(value.is_finite() && value >= 0.0) {
+                problems.push(format!("{} must not be negative (got {})", name, value));
+            }
+        }
+
+        let below_health = self.enemy.split.below_health;
+        if !(0.0..1.0).contains(&below_health) {
+            problems.push(format!(
+                "enemy.split.below_health must be between 0 and 1 (got {})",
+                below_health
+            ));
+        }
+
+        if self.waves.list.is_empty() {
+            problems.push("waves.list must contain at least one wave".to_string());
+        }
+        for (index, wave) in self.waves.list.iter().enumerate() {
+            if wave.groups.is_empty() {
+                problems.push(format!("waves.list[{}] has no enemies", index));
+            }
+            for group in wave.groups.iter() {
+                if group.count == 0 || group.walls.is_empty() || group.health_multiplier <= 0.0 {
+                    problems.push(format!(
+                        "waves.list[{}] needs a count, walls and a positive health multiplier \
+                        for every group",
+                        index
+                    ));
+                }
+            }
+        }
+
+        RonAssetError::check(problems)
+    }
+}
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // assets
+            .init_asset::<Balance>()
+            .register_asset_loader(RonAssetLoader::<Balance>::new(&["balance.ron"]))
+
+            // resources, replaced by the balance file once it is loaded
+            .insert_resource(Balance::bundled())
+
+            // startup systems
+            .add_systems(Startup, load_ron_file::<Balance>("game.balance.ron"))
+
+            // on-exit: asset loading
+            .add_systems(OnExit(GameState::AssetLoading), warn_if_not_loaded::<Balance>)
+
+            // update systems
+            .add_systems(Update, apply_loaded::<Balance>);
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use crate::gameplay::{Health, Mana};
+use crate::GameState;
+
+const TRAIL_DELAY: f32 = 0.4;
+
+const TRAIL_SPEED: f32 = 0.8;
+
+pub struct StatBarPlugin;
+
+impl Plugin for StatBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_stat_bars.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarStat {
+    Health,
+    Mana,
+}
+
+impl BarStat {
+    fn fill_color(self) -> Color {
+        match self {
+            BarStat::Health => Color::rgb(0.85, 0.2, 0.2),
+            BarStat::Mana => Color::rgb(0.3, 0.55, 1.0),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct StatBar {
+    pub target: Entity,
+    pub stat: BarStat,
+    pub size: Vec2,
+    pub fill: f32,
+    // fill plus the "recently lost" segment
+    pub trail: f32,
+    trail_delay: Timer,
+}
+
+impl StatBar {
+    pub fn new(target: Entity, stat: BarStat, size: Vec2) -> Self {
+        Self {
+            target,
+            stat,
+            size,
+            fill: 1.0,
+            trail: 1.0,
+            trail_delay: Timer::from_seconds(TRAIL_DELAY, TimerMode::Once),
+        }
+    }
+
+    fn set(&mut self, fill: f32, delta: std::time::Duration) {
+        if fill < self.fill {
+            self.trail_delay.reset();
+        }
+        self.fill = fill;
+
+        if self.trail <= self.fill {
+            self.trail = self.fill;
+        } else if self.trail_delay.tick(delta).finished() {
+            self.trail = (self.trail - TRAIL_SPEED * delta.as_secs_f32()).max(self.fill);
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct BarFill;
+
+#[derive(Component, Debug)]
+pub struct BarTrail;
+
+pub fn spawn_stat_bar(parent: &mut ChildBuilder, bar: StatBar, translation: Vec3) -> Entity {
+    let size = bar.size;
+    let fill_color = bar.stat.fill_color();
+    let left = Vec3::new(-size.x / 2.0, 0.0, 0.0);
+
+    parent
+        .spawn((SpatialBundle::from_transform(Transform::from_translation(translation)), bar))
+        .with_children(|parent| {
+            // border and background
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(size + Vec2::splat(2.0)),
+                    ..default()
+                },
+                ..default()
+            });
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(0.15, 0.15, 0.18),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                ..default()
+            });
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(1.0, 0.9, 0.6),
+                        custom_size: Some(size),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(left + Vec3::Z * 0.02),
+                    ..default()
+                },
+                BarTrail,
+            ));
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: fill_color,
+                        custom_size: Some(size),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(left + Vec3::Z * 0.03),
+                    ..default()
+                },
+                BarFill,
+            ));
+        })
+        .id()
+}
+
+fn update_stat_bars(
+    time: Res<Time>,
+    mut bar_query: Query<(&mut StatBar, &Children)>,
+    health_query: Query<&Health>,
+    mana_query: Query<&Mana>,
+    mut fill_query: Query<&mut Sprite, (With<BarFill>, Without<BarTrail>)>,
+    mut trail_query: Query<&mut Sprite, (With<BarTrail>, Without<BarFill>)>,
+) {
+    for (mut bar, children) in bar_query.iter_mut() {
+        let current = match bar.stat {
+            BarStat::Health => health_query.get(bar.target).ok().map(|health| (health.current, health.max)),
+            BarStat::Mana => mana_query.get(bar.target).ok().map(|mana| (mana.current, mana.max)),
+        };
+        let fill = match current {
+            Some((current, max)) if max > 0 => (current as f32 / max as f32).clamp(0.0, 1.0),
+            // the target is gone, e.g. the player died
+            _ => 0.0,
+        };
+        bar.set(fill, time.delta());
+
+        for child in children.iter() {
+            if let Ok(mut sprite) = fill_query.get_mut(*child) {
+                sprite.custom_size = Some(Vec2::new(bar.size.x * bar.fill, bar.size.y));
+            }
+            if let Ok(mut sprite) = trail_query.get_mut(*child) {
+                sprite.custom_size = Some(Vec2::new(bar.size.x * bar.trail, bar.size.y));
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use crate::gameplay::PlayerAction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapturedInput {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+impl CapturedInput {
+    fn kind(self) -> InputKind {
+        match self {
+            CapturedInput::Key(key) => InputKind::PhysicalKey(key),
+            CapturedInput::Gamepad(button) => InputKind::GamepadButton(button),
+        }
+    }
+
+    fn is_key(self) -> bool {
+        matches!(self, CapturedInput::Key(_))
+    }
+}
+
+pub fn action_name(action: PlayerAction) -> &'static str {
+    match action {
+        PlayerAction::Move => "Move",
+        PlayerAction::SpawnMinions => "Spawn Bombs",
+        PlayerAction::Pause => "Pause",
+    }
+}
+
+// the four directions of a virtual dpad included
+fn input_kinds(input: &UserInput) -> Vec<&InputKind> {
+    match input {
+        UserInput::Single(kind) => vec![kind],
+        UserInput::Chord(kinds) => kinds.iter().collect(),
+        UserInput::VirtualDPad(dpad) => vec![&dpad.up, &dpad.down, &dpad.left, &dpad.right],
+        UserInput::VirtualAxis(axis) => vec![&axis.negative, &axis.positive],
+    }
+}
+
+pub fn conflicting_action(
+    input_map: &InputMap<PlayerAction>,
+    action: PlayerAction,
+    input: CapturedInput,
+) -> Option<PlayerAction> {
+    let kind = input.kind();
+    input_map
+        .iter()
+        .filter(|(other, _)| **other != action)
+        .find(|(_, inputs)| inputs.iter().any(|input| input_kinds(input).contains(&&kind)))
+        .map(|(other, _)| *other)
+}
+
+// replaces the previous binding on the same device; fails with the conflicting action
+pub fn rebind_button(
+    input_map: &mut InputMap<PlayerAction>,
+    action: PlayerAction,
+    input: CapturedInput,
+) -> Result<(), PlayerAction> {
+    if let Some(other) = conflicting_action(input_map, action, input) {
+        return Err(other);
+    }
+
+    let mut inputs = input_map.get(&action).cloned().unwrap_or_default();
+    inputs.retain(|bound| match bound {
+        UserInput::Single(InputKind::PhysicalKey(_)) => !input.is_key(),
+        UserInput::Single(InputKind::GamepadButton(_)) => input.is_key(),
+        _ => true,
+    });
+    inputs.push(UserInput::Single(input.kind()));
+
+    input_map.clear_action(&action);
+    input_map.insert_one_to_many(action, inputs);
+    Ok(())
+}
+
+// keys go up, down, left, right; fails with the conflicting action
+pub fn rebind_movement(
+    input_map: &mut InputMap<PlayerAction>,
+    [up, down, left, right]: [KeyCode; 4],
+) -> Result<(), PlayerAction> {
+    for key in [up, down, left, right] {
+        if let Some(other) = conflicting_action(input_map, PlayerAction::Move, CapturedInput::Key(key)) {
+            return Err(other);
+        }
+    }
+
+    let mut inputs = input_map.get(&PlayerAction::Move).cloned().unwrap_or_default();
+    inputs.retain(|bound| !matches!(bound, UserInput::VirtualDPad(_)));
+    inputs.push(UserInput::VirtualDPad(VirtualDPad {
+        up: InputKind::PhysicalKey(up),
+        down: InputKind::PhysicalKey(down),
+        left: InputKind::PhysicalKey(left),
+        right: InputKind::PhysicalKey(right),
+    }));
+
+    input_map.clear_action(&PlayerAction::Move);
+    input_map.insert_one_to_many(PlayerAction::Move, inputs);
+    Ok(())
+}
+
+pub fn fill_missing_bindings(input_map: &mut InputMap<PlayerAction>) {
+    let defaults = PlayerAction::default_input_map();
+    for (action, inputs) in defaults.iter() {
+        if input_map.get(action).is_none_or(|bound| bound.is_empty()) {
+            input_map.insert_one_to_many(*action, inputs.iter().cloned());
+        }
+    }
+}
+
+// e.g. "WASD, Arrows, Left Stick"
+pub fn describe_bindings(input_map: &InputMap<PlayerAction>, action: PlayerAction) -> String {
+    let Some(inputs) = input_map.get(&action) else {
+        return "Unbound".to_string();
+    };
+
+    let names: Vec<String> = inputs.iter().map(describe_input).collect();
+    if names.is_empty() {
+        "Unbound".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn describe_input(input: &UserInput) -> String {
+    match input {
+        UserInput::VirtualDPad(dpad) if *dpad == VirtualDPad::wasd() => "WASD".to_string(),
+        UserInput::VirtualDPad(dpad) if *dpad == VirtualDPad::arrow_keys() => "Arrows".to_string(),
+        UserInput::VirtualDPad(dpad) => [&dpad.up, &dpad.left, &dpad.down, &dpad.right]
+            .map(describe_kind)
+            .join("/"),
+        UserInput::Single(kind) => describe_kind(kind),
+        other => input_kinds(other).into_iter().map(describe_kind).collect::<Vec<_>>().join("+"),
+    }
+}
+
+// e.g. "W" rather than "KeyW"
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+fn describe_kind(kind: &InputKind) -> String {
+    match kind {
+        InputKind::PhysicalKey(key) => key_name(*key),
+        InputKind::GamepadButton(button) => format!("Pad {:?}", button),
+        InputKind::DualAxis(axis) if *axis == DualAxis::left_stick() => "Left Stick".to_string(),
+        InputKind::DualAxis(axis) if *axis == DualAxis::right_stick() => "Right Stick".to_string(),
+        other => format!("{:?}", other),
+    }
+}
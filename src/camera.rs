@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use crate::settings::Settings;
+use crate::{CameraMarker, GameState, PauseState};
+
+// pixels at full trauma and full screen shake
+const MAX_OFFSET: f32 = 12.0;
+
+// radians at full trauma and full screen shake
+const MAX_ROLL: f32 = 0.03;
+
+const TRAUMA_DECAY: f32 = 1.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShakeKind {
+    Explosion,
+    PlayerHit,
+    SlimeDeath,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShakeSpec {
+    // trauma is capped at 1 and the shake grows with its square
+    pub trauma: f32,
+    pub hit_stop: f32,
+}
+
+impl ShakeKind {
+    pub fn spec(self) -> ShakeSpec {
+        let (trauma, hit_stop) = match self {
+            ShakeKind::Explosion => (0.35, 0.0),
+            ShakeKind::PlayerHit => (0.6, 0.08),
+            ShakeKind::SlimeDeath => (0.25, 0.04),
+        };
+        ShakeSpec { trauma, hit_stop }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShakeCamera(pub ShakeKind);
+
+#[derive(Resource, Debug, Default)]
+pub struct CameraTrauma {
+    pub trauma: f32,
+    elapsed: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct HitStop(pub Option<Timer>);
+
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // events
+            .add_event::<ShakeCamera>()
+
+            // resources
+            .init_resource::<CameraTrauma>()
+            .init_resource::<HitStop>()
+
+            // on-enter: paused / on-exit: in game
+            .add_systems(OnEnter(PauseState::Paused), (reset_shake, end_hit_stop))
+            .add_systems(OnExit(GameState::InGame), (reset_shake, end_hit_stop))
+
+            // update systems
+            .add_systems(Update, (
+                add_trauma,
+                tick_hit_stop,
+                shake_camera,
+            ).chain().run_if(in_state(GameState::InGame).and_then(in_state(PauseState::Running))));
+    }
+}
+
+fn add_trauma(
+    mut er_shake_camera: EventReader<ShakeCamera>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    for ShakeCamera(kind) in er_shake_camera.read() {
+        let spec = kind.spec();
+        trauma.trauma = (trauma.trauma + spec.trauma).min(1.0);
+
+        let remaining = hit_stop.0.as_ref().map_or(0.0, |timer| timer.remaining_secs());
+        if spec.hit_stop > remaining {
+            hit_stop.0 = Some(Timer::from_seconds(spec.hit_stop, TimerMode::Once));
+            virtual_time.set_relative_speed(0.0);
+        }
+    }
+}
+
+// in real time, as virtual time stands still during a hit-stop
+fn tick_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    if let Some(timer) = &mut hit_stop.0 {
+        if timer.tick(real_time.delta()).finished() {
+            hit_stop.0 = None;
+            virtual_time.set_relative_speed(1.0);
+        }
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut camera_query: Query<&mut Transform, With<CameraMarker>>,
+) {
+    trauma.trauma = (trauma.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+    trauma.elapsed += time.delta_seconds();
+
+    let shake = trauma.trauma * trauma.trauma * settings.screen_shake;
+    let t = trauma.elapsed;
+    // a few out of step sines make a cheap, smooth noise
+    let noise = |a: f32, b: f32| ((t * a).sin() + (t * b).sin()) / 2.0;
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = MAX_OFFSET * shake * noise(47.0, 71.0);
+        transform.translation.y = MAX_OFFSET * shake * noise(53.0, 67.0);
+        transform.rotation = Quat::from_rotation_z(MAX_ROLL * shake * noise(37.0, 59.0));
+    }
+}
+
+fn reset_shake(
+    mut trauma: ResMut<CameraTrauma>,
+    mut camera_query: Query<&mut Transform, With<CameraMarker>>,
+) {
+    *trauma = CameraTrauma::default();
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if hit_stop.0.take().is_some() {
+        virtual_time.set_relative_speed(1.0);
+    }
+}
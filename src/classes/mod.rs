@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
 
 // ----- Classes (they're really just callback functions that modify bundles / text styles, but it's useful to think of them as .css classes) -----
 pub fn c_root(b: &mut NodeBundle) {
@@ -13,6 +14,82 @@ pub fn c_no_bg(_b: &mut NodeBundle) {
     // no background
 }
 
+pub fn c_dim(b: &mut NodeBundle) {
+    b.background_color = Color::rgba(0.0, 0.0, 0.0, 0.6).into();
+}
+
+pub fn c_overlay(b: &mut NodeBundle) {
+    b.background_color = Color::rgba(0.05, 0.05, 0.1, 0.95).into();
+    b.focus_policy = FocusPolicy::Block;
+    b.z_index = ZIndex::Global(10);
+}
+
+pub fn c_row(b: &mut NodeBundle) {
+    b.style.flex_direction = FlexDirection::Row;
+    b.style.align_items = AlignItems::Center;
+}
+
+pub fn c_column(b: &mut NodeBundle) {
+    b.style.flex_direction = FlexDirection::Column;
+    b.style.align_items = AlignItems::Center;
+    b.style.margin = UiRect::bottom(Val::Px(10.0));
+}
+
+pub fn c_achievement(b: &mut NodeBundle) {
+    b.style.flex_direction = FlexDirection::Column;
+    b.style.width = Val::Px(560.0);
+    b.style.margin = UiRect::all(Val::Px(4.0));
+}
+
+pub fn c_toast_area(b: &mut NodeBundle) {
+    b.style.position_type = PositionType::Absolute;
+    b.style.top = Val::Px(10.0);
+    b.style.right = Val::Px(10.0);
+    b.style.flex_direction = FlexDirection::Column;
+    b.style.align_items = AlignItems::End;
+    b.z_index = ZIndex::Global(20);
+}
+
+pub fn c_toast(b: &mut NodeBundle) {
+    b.style.padding = UiRect::all(Val::Px(8.0));
+    b.style.margin = UiRect::bottom(Val::Px(6.0));
+    b.background_color = Color::rgba(0.05, 0.05, 0.1, 0.9).into();
+}
+
+pub fn c_slider(_a: &AssetServer, b: &mut ButtonBundle) {
+    let s = &mut b.style;
+    s.width = Val::Px(240.0);
+    s.height = Val::Px(20.0);
+    s.margin = UiRect::all(Val::Px(5.0));
+    s.border = UiRect::all(Val::Px(2.0));
+    b.border_color = Color::WHITE.into();
+    b.background_color = Color::rgb_u8(30, 30, 40).into();
+}
+
+pub fn c_slider_fill(b: &mut NodeBundle) {
+    b.style.height = Val::Percent(100.);
+    b.background_color = Color::rgb_u8(66, 135, 245).into();
+}
+
+pub fn c_setting_label(_a: &AssetServer, b: &mut TextBundle) {
+    b.style.width = Val::Px(240.0);
+    b.style.margin = UiRect::all(Val::Px(5.0));
+}
+
+pub fn c_action_label(_a: &AssetServer, b: &mut TextBundle) {
+    b.style.width = Val::Px(170.0);
+    b.style.margin = UiRect::all(Val::Px(5.0));
+}
+
+pub fn c_binding_label(_a: &AssetServer, b: &mut TextBundle) {
+    b.style.width = Val::Px(400.0);
+    b.style.margin = UiRect::all(Val::Px(5.0));
+}
+
+pub fn c_stat_line(_a: &AssetServer, b: &mut TextBundle) {
+    b.style.margin = UiRect::all(Val::Px(4.0));
+}
+
 pub fn c_text(_a: &AssetServer, b: &mut TextBundle) {
     b.style.margin = UiRect::all(Val::Px(10.));
 }
@@ -27,12 +104,30 @@ pub fn c_button(assets: &AssetServer, b: &mut ButtonBundle) {
     b.image = assets.load("images/Sprite-Button.png").into();
 }
 
+pub fn c_menu_button(_a: &AssetServer, b: &mut ButtonBundle) {
+    b.style.margin = UiRect::all(Val::Px(5.0));
+}
+
+pub fn c_wide_button(_a: &AssetServer, b: &mut ButtonBundle) {
+    b.style.width = Val::Px(240.0);
+}
+
 pub fn c_pixel_title(assets: &AssetServer, s: &mut TextStyle) {
     s.font = assets.load("fonts/prstartk.ttf");
     s.font_size = 24.0;
     s.color = Color::WHITE;
 }
 
+pub fn c_pixel_small(assets: &AssetServer, s: &mut TextStyle) {
+    s.font = assets.load("fonts/prstartk.ttf");
+    s.font_size = 10.0;
+    s.color = Color::WHITE;
+}
+
+pub fn c_locked(_a: &AssetServer, s: &mut TextStyle) {
+    s.color = Color::GRAY;
+}
+
 pub fn c_pixel_button(assets: &AssetServer, s: &mut TextStyle) {
     s.font = assets.load("fonts/prstartk.ttf");
     s.font_size = 14.0;
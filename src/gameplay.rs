@@ -0,0 +1,1576 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy_xpbd_2d::math::Vector;
+use bevy_xpbd_2d::prelude::*;
+use leafwing_input_manager::plugin::InputManagerPlugin;
+use leafwing_input_manager::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::time::Duration;
+use crate::animation::{advance_animations, spawn_corpse, AnimationSet, SpriteAnimation};
+use crate::audio::*;
+use crate::balance::{Balance, EnemyKind, WaveGroup, Wall};
+use crate::bars::{spawn_stat_bar, BarStat, StatBar, StatBarPlugin};
+use crate::bindings::{action_name, describe_bindings};
+use crate::camera::{ShakeCamera, ShakeKind};
+use crate::interpolation::*;
+use crate::loading::*;
+use crate::particles::{ParticleEffect, SpawnParticles};
+use crate::popups::{PopupKind, SpawnPopup};
+use crate::rng::*;
+use crate::settings::Settings;
+use crate::stats::MatchStats;
+use crate::{
+    FontResource, GameState, PauseState, SettingsState, HALF_HEIGHT, HALF_WIDTH, WINDOW_HEIGHT,
+    WINDOW_WIDTH,
+};
+
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+
+// the simulation only, without a window, audio or menus, so it can also run headless
+pub struct GameplayPlugin {
+    pub tick_rate: f64,
+}
+
+impl Default for GameplayPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // plugins
+            .add_plugins(InputManagerPlugin::<PlayerAction>::default())
+            .add_plugins(PhysicsPlugins::new(FixedUpdate))
+            .add_plugins(InterpolationPlugin)
+            .add_plugins(StatBarPlugin)
+            // .add_plugins(PhysicsDebugPlugin::default())
+
+            // events
+            .add_event::<SpawnMinionEvent>()
+            .add_event::<DamageTakenEvent>()
+            .add_event::<ManaGainedEvent>()
+            .add_event::<ExplosionEvent>()
+            .add_event::<PlaySfx>()
+            .add_event::<SpawnParticles>()
+            .add_event::<ShakeCamera>()
+            .add_event::<SpawnPopup>()
+
+            // resources
+            .init_resource::<SeedConfig>()
+            .init_resource::<GameMode>()
+            .insert_resource(TickRate(self.tick_rate))
+            .insert_resource(Time::new_with(Physics::fixed_once_hz(self.tick_rate)))
+            .insert_resource(Time::<Fixed>::from_hz(self.tick_rate))
+
+            // states
+            .init_state::<PauseState>()
+
+            // on-enter: in game
+            .add_systems(OnEnter(GameState::InGame), (
+                reset_game_status,
+                setup_game_rng,
+                setup_game.after(setup_game_rng),
+                spawn_player.after(setup_game),
+                setup_waves.after(spawn_player),
+                setup_mana_spawning,
+            ))
+
+            // on-enter / on-exit: paused
+            .add_systems(OnEnter(PauseState::Paused), pause_time)
+            .add_systems(OnExit(PauseState::Paused), resume_time)
+
+            // on-enter: restarting
+            .add_systems(OnEnter(GameState::Restarting), restart_match)
+
+            // fixed update systems, chained so every match plays out the same way given the same
+            // seed and input (required for replays)
+            .add_systems(FixedUpdate, (
+                handle_actions,
+                apply_knockback,
+                tick_invulnerability,
+                minion_spawner,
+                slime_movement,
+                dasher_movement,
+                ranged_movement,
+                ranged_attack,
+                tank_movement,
+                minion_movement,
+                handle_collisions,
+                burn_fuses,
+                handle_damage_taken,
+                split_slimes,
+                advance_waves,
+                handle_mana_gained,
+                mana_spawner,
+                tick_match_stats,
+            ).chain()
+                .before(PhysicsSet::Prepare)
+                .run_if(in_state(GameState::InGame).and_then(match_in_progress)))
+
+            // update systems
+            .add_systems(Update, (
+                apply_tick_rate.run_if(resource_changed::<TickRate>),
+                toggle_pause.run_if(
+                    in_state(GameState::InGame).and_then(match_in_progress).and_then(settings_closed),
+                ),
+                (
+                    sample_player_input.run_if(in_state(PauseState::Running)),
+                    apply_bindings.run_if(resource_changed::<Settings>),
+                    update_dasher_telegraphs.after(advance_animations),
+                    update_invulnerability_blink,
+                    update_wave_banner,
+                ).run_if(in_state(GameState::InGame)),
+            ))
+
+            // on exit: in game
+            .add_systems(OnExit(GameState::InGame), (
+                cleanup_in_game_screen,
+            ))
+
+            // resources
+            .insert_resource(GameStatus {
+                result: GameResult::None,
+                stats: MatchStats::default(),
+            });
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TickRate(pub f64);
+
+fn apply_tick_rate(
+    tick_rate: Res<TickRate>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    fixed_time.set_timestep_hz(tick_rate.0);
+    physics_time.set_timestep_mode(TimestepMode::FixedOnce {
+        delta: fixed_time.timestep(),
+    });
+}
+
+#[derive(Component, Debug)]
+pub struct Player;
+
+#[derive(Component, Debug)]
+pub struct Minion;
+
+#[derive(Component, Debug)]
+pub struct Enemy;
+
+// generation 0 is the first slime of a match, its children generation 1 and so on
+#[derive(Component, Debug)]
+pub struct Slime {
+    pub generation: u32,
+}
+
+#[derive(Component, Debug)]
+pub struct Dasher {
+    pub phase: DashPhase,
+    pub timer: Timer,
+    // locked in when the telegraph ends
+    pub direction: Vector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashPhase {
+    Approach,
+    Telegraph,
+    Lunge,
+}
+
+#[derive(Component, Debug)]
+pub struct RangedSlime {
+    pub fire_timer: Timer,
+}
+
+#[derive(Component, Debug)]
+pub struct Tank;
+
+#[derive(Component, Debug)]
+pub struct Projectile {
+    pub damage: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Mana {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+pub struct DamageDone(pub i32);
+
+#[derive(Component, Debug)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
+
+#[derive(Component, Debug)]
+pub struct Knockback {
+    pub velocity: Vector,
+}
+
+#[derive(Component, Debug)]
+pub struct Fuse {
+    pub timer: Timer,
+}
+
+// sampled from live input every frame, or fed from a replay
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq)]
+pub struct PlayerInput {
+    pub movement: Option<Vec2>,
+    // latched until a tick consumes it, so a press is never lost or repeated
+    pub spawn_minions: bool,
+}
+
+#[derive(Event, Debug)]
+pub struct SpawnMinionEvent(pub f32);
+
+#[derive(Event, Debug)]
+pub struct DamageTakenEvent {
+    pub giver: Entity,
+    pub receiver: Entity,
+    pub amount: i32,
+}
+
+#[derive(Event, Debug)]
+pub struct ExplosionEvent {
+    pub bomb: Entity,
+    pub position: Vector,
+    pub radius: f32,
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    Endless,
+}
+
+#[derive(Resource, Debug)]
+pub struct WaveManager {
+    pub wave: usize,
+    pub intermission: Option<Timer>,
+}
+
+#[derive(Component, Debug)]
+pub struct WaveBanner;
+
+#[derive(Component, Debug)]
+pub struct ManaGem(pub i32);
+
+#[derive(Resource, Debug)]
+pub struct ManaSpawnConfig {
+    pub timer: Timer,
+}
+
+#[derive(Event, Debug)]
+pub struct ManaGainedEvent {
+    pub player: Entity,
+    pub mana_gem: Entity,
+    pub amount: i32,
+}
+
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect, Serialize, Deserialize)]
+pub enum PlayerAction {
+    Move,
+    SpawnMinions,
+    Pause,
+}
+
+impl PlayerAction {
+    pub const ALL: [Self; 3] = [Self::Move, Self::SpawnMinions, Self::Pause];
+
+    pub fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        // keyboard
+        input_map.insert(Self::Move, VirtualDPad::wasd());
+        input_map.insert(Self::Move, VirtualDPad::arrow_keys());
+        input_map.insert(Self::SpawnMinions, KeyCode::Space);
+        input_map.insert(Self::Pause, KeyCode::Escape);
+
+        // gamepad
+        input_map.insert(Self::Move, DualAxis::left_stick());
+        input_map.insert(Self::SpawnMinions, GamepadButtonType::South);
+        input_map.insert(Self::Pause, GamepadButtonType::Start);
+
+        input_map
+    }
+}
+
+#[derive(PhysicsLayer)]
+pub enum GameLayer {
+    Player, // Layer 0
+    Minion, // Layer 1
+    Enemy,  // Layer 3
+    Gems,   // Layer 4
+    Projectile, // Layer 5
+}
+
+#[derive(Component)]
+pub struct InGameScreen;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    None,
+    Win,
+    Lose,
+}
+
+#[derive(Resource)]
+pub struct GameStatus {
+    pub result: GameResult,
+    pub stats: MatchStats,
+}
+
+pub fn match_in_progress(game_status: Res<GameStatus>) -> bool {
+    game_status.result == GameResult::None
+}
+
+fn reset_game_status(mut game_status: ResMut<GameStatus>) {
+    game_status.result = GameResult::None;
+    game_status.stats = MatchStats::default();
+}
+
+pub fn tick_match_stats(time: Res<Time>, mut game_status: ResMut<GameStatus>) {
+    game_status.stats.tick(time.delta_seconds());
+}
+
+// always true when there are no settings screens at all
+fn settings_closed(settings_state: Option<Res<State<SettingsState>>>) -> bool {
+    settings_state.is_none_or(|state| *state.get() == SettingsState::Closed)
+}
+
+fn restart_match(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::InGame);
+}
+
+fn toggle_pause(
+    action_query: Query<&ActionState<PlayerAction>, With<Player>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if action_query.iter().any(|action_state| action_state.just_pressed(&PlayerAction::Pause)) {
+        next_pause_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+// physics keeps its own clock, so it has to be paused as well
+fn pause_time(mut virtual_time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    virtual_time.pause();
+    physics_time.pause();
+}
+
+fn resume_time(mut virtual_time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    virtual_time.unpause();
+    physics_time.unpause();
+}
+
+fn cleanup_in_game_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<InGameScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_game(
+    mut commands: Commands,
+    font_res: Res<FontResource>,
+    settings: Res<Settings>,
+) {
+    // spawn some instructions, matching the player's bindings
+    let instructions: Vec<String> = PlayerAction::ALL
+        .iter()
+        .map(|action| {
+            format!("{}: {}", action_name(*action), describe_bindings(&settings.bindings, *action))
+        })
+        .collect();
+    commands.spawn((Text2dBundle {
+        text: Text::from_section(
+            instructions.join(" | "),
+            TextStyle {
+                font: font_res.font.clone(),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+        ),
+        text_anchor: Anchor::TopLeft,
+        transform: Transform {
+            translation: Vec3::new(-HALF_WIDTH, HALF_HEIGHT, 0.0),
+            ..default()
+        },
+        ..default()
+    },InGameScreen));
+    commands.spawn((
+        // TODO: make this a section
+        Text2dBundle {
+            text: Text::from_section(
+                "Don't get hit. Spawn bombs, collect mana gems. Survive.",
+                TextStyle {
+                    font: font_res.font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform {
+                translation: Vec3::new(-HALF_WIDTH, HALF_HEIGHT - 20.0, 0.0),
+                ..default()
+            },
+            ..default()
+        },InGameScreen
+    ));
+
+    // spawn the (hidden) banner shown between waves
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font_res.font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            visibility: Visibility::Hidden,
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        WaveBanner,
+        InGameScreen,
+    ));
+
+    // create the top
+    commands
+        .spawn(RigidBody::Static)
+        .insert(Collider::rectangle(WINDOW_WIDTH, 1.0))
+        .insert(Name::new("Wall_Top"))
+        .insert(TransformBundle::from(Transform::from_xyz(
+            0.0,
+            HALF_HEIGHT,
+            0.0,
+        ))).insert(InGameScreen);
+
+    // create the left wall
+    commands
+        .spawn(RigidBody::Static)
+        .insert(Collider::rectangle(1.0, WINDOW_HEIGHT))
+        .insert(Name::new("Wall_Left"))
+        .insert(TransformBundle::from(Transform::from_xyz(
+            -HALF_WIDTH,
+            0.0,
+            0.0,
+        ))).insert(InGameScreen);
+
+    // create the right wall
+    commands
+        .spawn(RigidBody::Static)
+        .insert(Collider::rectangle(1.0, WINDOW_HEIGHT))
+        .insert(Name::new("Wall_Right"))
+        .insert(TransformBundle::from(Transform::from_xyz(
+            HALF_WIDTH, 0.0, 0.0,
+        ))).insert(InGameScreen);
+
+    // create the bottom
+    commands
+        .spawn(RigidBody::Static)
+        .insert(Collider::rectangle(WINDOW_WIDTH, 1.0))
+        .insert(Name::new("Wall_Bottom"))
+        .insert(TransformBundle::from(Transform::from_xyz(
+            0.0,
+            -HALF_HEIGHT,
+            0.0,
+        ))).insert(InGameScreen);
+}
+
+pub fn spawn_player(
+    mut commands: Commands,
+    sprite_res: Res<SpriteAssets>,
+    font_res: Res<FontResource>,
+    balance: Res<Balance>,
+    settings: Res<Settings>,
+) {
+    let position = balance.player.start_position();
+
+    // configure and spawn the player
+    let player = commands
+        .spawn(Player)
+        .insert(Name::new("Player"))
+        .insert(RigidBody::Kinematic)
+        .insert(Collider::circle(balance.player.radius))
+        .insert(GravityScale(0.0))
+        .insert(Mass(10.0))
+        .insert(Restitution::new(0.0))
+        .insert(Position(position))
+        .insert(CollisionLayers::new(
+            GameLayer::Player,
+            [GameLayer::Enemy, GameLayer::Gems, GameLayer::Projectile],
+        ))
+        .insert(SpriteBundle {
+            texture: sprite_res.player.clone(),
+            ..default()
+        })
+        .insert(SpriteAnimation::new(sprite_res.player_animations.clone()))
+        .insert(InputManagerBundle::with_map(
+            settings.bindings.clone(),
+        ))
+        .insert(PlayerInput::default())
+        .insert(InterpolatedPosition::new(position))
+        .insert(Health {
+            current: balance.player.health,
+            max: balance.player.health,
+        })
+        .insert(Mana {
+            current: balance.player.mana,
+            max: balance.player.mana,
+        })
+        .insert(DamageDone(0))
+        .insert(InGameScreen)
+        .id();
+
+    // spawn the player's health and mana bars in the bottom right corner, away from where the
+    // player starts
+    let bar_size = Vec2::new(160.0, 10.0);
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                HALF_WIDTH - 16.0 - bar_size.x / 2.0,
+                -HALF_HEIGHT + 32.0,
+                5.0,
+            )),
+            Name::new("PlayerBars"),
+            InGameScreen,
+        ))
+        .with_children(|parent| {
+            for (y, stat, label) in [(9.0, BarStat::Health, "HP"), (-9.0, BarStat::Mana, "MP")] {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font: font_res.font.clone(),
+                            font_size: 16.0,
+                            color: Color::ALICE_BLUE,
+                        },
+                    ),
+                    text_anchor: Anchor::CenterRight,
+                    transform: Transform::from_xyz(-bar_size.x / 2.0 - 6.0, y, 0.0),
+                    ..default()
+                });
+                spawn_stat_bar(parent, StatBar::new(player, stat, bar_size), Vec3::new(0.0, y, 0.0));
+            }
+        });
+}
+
+fn setup_waves(
+    mut commands: Commands,
+    sprite_res: Res<SpriteAssets>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
+) {
+    spawn_wave(&mut commands, &sprite_res, &balance, &mut rng, 0);
+    commands.insert_resource(WaveManager {
+        wave: 0,
+        intermission: None,
+    });
+}
+
+fn spawn_wave(
+    commands: &mut Commands,
+    sprite_res: &SpriteAssets,
+    balance: &Balance,
+    rng: &mut GameRng,
+    wave: usize,
+) {
+    info!("Wave {} begins.", wave + 1);
+
+    for group in balance.waves.groups(wave) {
+        spawn_wave_group(commands, sprite_res, balance, rng, &group);
+    }
+}
+
+fn spawn_wave_group(
+    commands: &mut Commands,
+    sprite_res: &SpriteAssets,
+    balance: &Balance,
+    rng: &mut GameRng,
+    group: &WaveGroup,
+) {
+    let scaled = |health: i32| {
+        cmp::max(1, (health as f32 * group.health_multiplier).round() as i32)
+    };
+
+    for i in 0..group.count as usize {
+        let wall = group.walls[i % group.walls.len()];
+        match group.kind {
+            EnemyKind::Slime => {
+                let position = wall_spawn_position(wall, balance.enemy.radius, rng);
+                let health = scaled(balance.enemy.health);
+                spawn_slime(commands, sprite_res, balance, position, 0, health);
+            }
+            EnemyKind::Dasher => {
+                let dasher = &balance.dasher;
+                let position = wall_spawn_position(wall, dasher.radius, rng);
+                spawn_enemy_body(
+                    commands,
+                    sprite_res.dasher.clone(),
+                    sprite_res.enemy_animations.clone(),
+                    dasher.radius,
+                    dasher.mass,
+                    position,
+                    scaled(dasher.health),
+                    dasher.damage,
+                )
+                .insert(Name::new("Dasher"))
+                .insert(Dasher {
+                    phase: DashPhase::Approach,
+                    timer: Timer::from_seconds(dasher.cooldown, TimerMode::Once),
+                    direction: Vector::ZERO,
+                });
+            }
+            EnemyKind::Ranged => {
+                let ranged = &balance.ranged;
+                let position = wall_spawn_position(wall, ranged.radius, rng);
+                spawn_enemy_body(
+                    commands,
+                    sprite_res.ranged_slime.clone(),
+                    sprite_res.enemy_animations.clone(),
+                    ranged.radius,
+                    ranged.mass,
+                    position,
+                    scaled(ranged.health),
+                    ranged.damage,
+                )
+                .insert(Name::new("RangedSlime"))
+                .insert(RangedSlime {
+                    fire_timer: Timer::from_seconds(ranged.fire_interval, TimerMode::Repeating),
+                });
+            }
+            EnemyKind::Tank => {
+                let tank = &balance.tank;
+                let position = wall_spawn_position(wall, tank.radius, rng);
+                spawn_enemy_body(
+                    commands,
+                    sprite_res.tank.clone(),
+                    sprite_res.enemy_animations.clone(),
+                    tank.radius,
+                    tank.mass,
+                    position,
+                    scaled(tank.health),
+                    tank.damage,
+                )
+                .insert(Name::new("Tank"))
+                .insert(Tank);
+            }
+        }
+    }
+}
+
+fn wall_spawn_position(wall: Wall, radius: f32, rng: &mut GameRng) -> Vector {
+    let gap = radius + 5.0;
+    let x = rng.gen_range(-HALF_WIDTH + gap..=HALF_WIDTH - gap);
+    let y = rng.gen_range(-HALF_HEIGHT + gap..=HALF_HEIGHT - gap);
+    match wall {
+        Wall::Top => Vector::new(x, HALF_HEIGHT - gap),
+        Wall::Bottom => Vector::new(x, -HALF_HEIGHT + gap),
+        Wall::Left => Vector::new(-HALF_WIDTH + gap, y),
+        Wall::Right => Vector::new(HALF_WIDTH - gap, y),
+    }
+}
+
+fn spawn_slime(
+    commands: &mut Commands,
+    sprite_res: &SpriteAssets,
+    balance: &Balance,
+    position: Vector,
+    generation: u32,
+    health: i32,
+) {
+    spawn_enemy_body(
+        commands,
+        sprite_res.enemy.clone(),
+        sprite_res.enemy_animations.clone(),
+        balance.enemy.radius_at(generation),
+        balance.enemy.mass_at(generation),
+        position,
+        health,
+        balance.enemy.damage,
+    )
+    .insert(Name::new("Enemy"))
+    .insert(Slime { generation });
+}
+
+fn spawn_enemy_body<'a>(
+    commands: &'a mut Commands,
+    texture: Handle<Image>,
+    animations: Handle<AnimationSet>,
+    radius: f32,
+    mass: f32,
+    position: Vector,
+    health: i32,
+    damage: i32,
+) -> EntityCommands<'a> {
+    let mut entity = commands.spawn(Enemy);
+    let id = entity.id();
+    entity
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::circle(radius))
+        .insert(GravityScale(0.0))
+        .insert(Mass(mass))
+        .insert(Restitution::new(0.0))
+        .insert(LinearDamping(0.8))
+        .insert(AngularDamping(1.6))
+        .insert(CollisionLayers::new(
+            GameLayer::Enemy,
+            [GameLayer::Player, GameLayer::Minion],
+        ))
+        .insert(Position(position))
+        .insert(InterpolatedPosition::new(position))
+        .insert(SpriteBundle {
+            texture,
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(radius * 2.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SpriteAnimation::new(animations).with_base_size(Vec2::splat(radius * 2.0)))
+        .insert(Health {
+            current: health,
+            max: health,
+        })
+        .insert(DamageDone(damage))
+        .with_children(|parent| {
+            spawn_stat_bar(
+                parent,
+                StatBar::new(id, BarStat::Health, Vec2::new(radius * 2.0, 5.0)),
+                Vec3::new(0.0, radius + 6.0, 0.1),
+            );
+        })
+        .insert(InGameScreen);
+    entity
+}
+
+fn minion_spawner(
+    mut commands: Commands,
+    mut er_spawn_minion: EventReader<SpawnMinionEvent>,
+    player_pos_query: Query<&Position, With<Player>>,
+    sprite_res: Res<SpriteAssets>,
+    balance: Res<Balance>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    let player_radius = balance.player.radius;
+    let minion_radius = balance.minion.radius;
+
+    for event in er_spawn_minion.read() {
+        let player_pos = player_pos_query.single().0; // FIXME: this will panic if the player dies in the middle of spawning
+
+        let gap = 5.0;
+        let minion_pos = Vector::new(
+            player_pos.x + player_radius + (gap + minion_radius) * (event.0 + 1.0),
+            player_pos.y + player_radius + (gap + minion_radius) * (event.0 + 1.0),
+        );
+
+        debug!("Spawning new minion (#{}) at {}.", event.0, player_pos);
+        game_status.stats.record_bomb_spawned();
+
+        commands
+            .spawn(Minion)
+            .insert(Name::new("Minion"))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::circle(minion_radius))
+            .insert(GravityScale(0.0))
+            .insert(Mass(50.0))
+            .insert(Restitution::new(1.0))
+            .insert(LinearDamping(0.8))
+            .insert(AngularDamping(1.6))
+            .insert(CollisionLayers::new(
+                GameLayer::Minion,
+                [GameLayer::Minion, GameLayer::Enemy],
+            ))
+            .insert(Position(minion_pos))
+            .insert(InterpolatedPosition::new(minion_pos))
+            .insert(SpriteBundle {
+                texture: sprite_res.minion.clone(),
+                ..default()
+            })
+            .insert(SpriteAnimation::new(sprite_res.minion_animations.clone()))
+            .insert(Fuse {
+                timer: Timer::from_seconds(balance.minion.fuse, TimerMode::Once),
+            })
+            .insert(InGameScreen);
+    }
+}
+
+fn apply_bindings(
+    settings: Res<Settings>,
+    mut input_map_query: Query<&mut InputMap<PlayerAction>, With<Player>>,
+) {
+    for mut input_map in input_map_query.iter_mut() {
+        *input_map = settings.bindings.clone();
+    }
+}
+
+fn sample_player_input(
+    mut input_query: Query<
+        (&ActionState<PlayerAction>, &mut PlayerInput),
+        (With<Player>, With<InputMap<PlayerAction>>),
+    >,
+) {
+    for (action_state, mut input) in input_query.iter_mut() {
+        input.movement = if action_state.pressed(&PlayerAction::Move) {
+            action_state
+                .clamped_axis_pair(&PlayerAction::Move)
+                .map(|axis_pair| axis_pair.xy())
+        } else {
+            None
+        };
+
+        if action_state.just_pressed(&PlayerAction::SpawnMinions) {
+            input.spawn_minions = true;
+        }
+    }
+}
+
+pub fn handle_actions(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    mut input_query: Query<&mut PlayerInput, With<Player>>,
+    mut player_xform_query: Query<&mut Position, With<Player>>,
+    mut player_mana_query: Query<&mut Mana, With<Player>>,
+    mut ew_spawn_minion: EventWriter<SpawnMinionEvent>,
+    mut ew_play_sfx: EventWriter<PlaySfx>,
+) {
+    for mut input in input_query.iter_mut() {
+        let speed = balance.player.speed * time.delta_seconds();
+        let radius = balance.player.radius;
+
+        if let Some(movement) = input.movement {
+            let move_delta = speed * movement;
+
+            if let Ok(mut position) = player_xform_query.get_single_mut() {
+                // clamp x position within the window
+                if (position.x + move_delta.x < HALF_WIDTH - radius)
+                    && (position.x + move_delta.x > -HALF_WIDTH + radius)
+                {
+                    position.x += move_delta.x;
+                }
+
+                // clamp y position within the window
+                if (position.y + move_delta.y < HALF_HEIGHT - radius)
+                    && (position.y + move_delta.y > -HALF_HEIGHT + radius)
+                {
+                    position.y += move_delta.y;
+                }
+            }
+        }
+
+        if input.spawn_minions {
+            input.spawn_minions = false;
+
+            let mana_cost = balance.player.spawn_cost;
+            if let Ok(mut mana) = player_mana_query.get_single_mut() {
+                // TODO: move this logic to the minion spawner
+                if mana.current >= mana_cost {
+                    ew_play_sfx.send(PlaySfx::new(SfxKind::SpawnMinion));
+
+                    mana.current -= mana_cost;
+
+                    for i in 1..=2 {
+                        ew_spawn_minion.send(SpawnMinionEvent(i as f32));
+                    }
+                } else {
+                    ew_play_sfx.send(PlaySfx::new(SfxKind::OutOfMana));
+                }
+            }
+        }
+    }
+}
+
+fn slime_movement(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    target_query: Query<&Position, With<Player>>,
+    mut chaser_query: Query<(&Position, &mut LinearVelocity, &Slime)>,
+) {
+    if let Ok(pos_target) = target_query.get_single() {
+        for (pos_chaser, mut linear_vel, slime) in chaser_query.iter_mut() {
+            let speed = balance.enemy.speed_at(slime.generation) * time.delta_seconds();
+            let direction = Vec2::normalize(pos_target.0 - pos_chaser.0);
+            linear_vel.x += direction.x * speed;
+            linear_vel.y += direction.y * speed;
+        }
+    }
+}
+
+fn dasher_movement(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    target_query: Query<&Position, With<Player>>,
+    mut dasher_query: Query<(&mut Dasher, &Position, &mut LinearVelocity)>,
+) {
+    let Ok(pos_target) = target_query.get_single() else {
+        return;
+    };
+    let dasher_balance = &balance.dasher;
+
+    for (mut dasher, position, mut linear_vel) in dasher_query.iter_mut() {
+        let direction = Vec2::normalize_or_zero(pos_target.0 - position.0);
+        let finished = dasher.timer.tick(time.delta()).finished();
+
+        match dasher.phase {
+            DashPhase::Approach => {
+                linear_vel.0 += direction * dasher_balance.speed * time.delta_seconds();
+                if finished {
+                    dasher.phase = DashPhase::Telegraph;
+                    dasher.timer = Timer::from_seconds(dasher_balance.telegraph, TimerMode::Once);
+                }
+            }
+            DashPhase::Telegraph => {
+                linear_vel.0 *= (1.0 - 10.0 * time.delta_seconds()).max(0.0);
+                if finished {
+                    dasher.phase = DashPhase::Lunge;
+                    dasher.timer = Timer::from_seconds(dasher_balance.lunge, TimerMode::Once);
+                    dasher.direction = direction;
+                }
+            }
+            DashPhase::Lunge => {
+                linear_vel.0 = dasher.direction * dasher_balance.lunge_speed;
+                if finished {
+                    dasher.phase = DashPhase::Approach;
+                    dasher.timer = Timer::from_seconds(dasher_balance.cooldown, TimerMode::Once);
+                }
+            }
+        }
+    }
+}
+
+fn update_dasher_telegraphs(mut dasher_query: Query<(&Dasher, &mut Sprite)>) {
+    for (dasher, mut sprite) in dasher_query.iter_mut() {
+        if dasher.phase == DashPhase::Telegraph {
+            let lit = (dasher.timer.elapsed_secs() * 12.0).fract() < 0.5;
+            sprite.color = if lit { Color::YELLOW } else { Color::WHITE };
+        } else if sprite.color == Color::YELLOW {
+            // otherwise the colour belongs to the animation, e.g. the hurt tint
+            sprite.color = Color::WHITE;
+        }
+    }
+}
+
+fn ranged_movement(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    target_query: Query<&Position, With<Player>>,
+    mut ranged_query: Query<(&Position, &mut LinearVelocity), With<RangedSlime>>,
+) {
+    let Ok(pos_target) = target_query.get_single() else {
+        return;
+    };
+    let ranged = &balance.ranged;
+
+    for (position, mut linear_vel) in ranged_query.iter_mut() {
+        let offset = pos_target.0 - position.0;
+        let direction = Vec2::normalize_or_zero(offset);
+        let towards = if offset.length() > ranged.keep_distance { 1.0 } else { -1.0 };
+        linear_vel.0 += towards * direction * ranged.speed * time.delta_seconds();
+    }
+}
+
+fn ranged_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<Balance>,
+    sprite_res: Res<SpriteAssets>,
+    target_query: Query<&Position, With<Player>>,
+    mut ranged_query: Query<(&mut RangedSlime, &Position)>,
+) {
+    let Ok(pos_target) = target_query.get_single() else {
+        return;
+    };
+    let ranged = &balance.ranged;
+
+    for (mut ranged_slime, position) in ranged_query.iter_mut() {
+        if !ranged_slime.fire_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let direction = Vec2::normalize_or_zero(pos_target.0 - position.0);
+        let projectile_pos =
+            position.0 + direction * (ranged.radius + ranged.projectile_radius + 2.0);
+
+        commands
+            .spawn(Projectile {
+                damage: ranged.projectile_damage,
+            })
+            .insert(Name::new("Projectile"))
+            .insert(RigidBody::Kinematic)
+            .insert(Collider::circle(ranged.projectile_radius))
+            .insert(CollisionLayers::new(GameLayer::Projectile, [GameLayer::Player]))
+            .insert(Position(projectile_pos))
+            .insert(LinearVelocity(direction * ranged.projectile_speed))
+            .insert(InterpolatedPosition::new(projectile_pos))
+            .insert(SpriteBundle {
+                texture: sprite_res.projectile.clone(),
+                ..default()
+            })
+            .insert(InGameScreen);
+    }
+}
+
+fn tank_movement(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    target_query: Query<&Position, With<Player>>,
+    mut tank_query: Query<(&Position, &mut LinearVelocity), With<Tank>>,
+) {
+    if let Ok(pos_target) = target_query.get_single() {
+        let speed = balance.tank.speed * time.delta_seconds();
+
+        for (position, mut linear_vel) in tank_query.iter_mut() {
+            linear_vel.0 += Vec2::normalize_or_zero(pos_target.0 - position.0) * speed;
+        }
+    }
+}
+
+fn minion_movement(
+    time: Res<Time>,
+    balance: Res<Balance>,
+    target_query: Query<&Position, With<Enemy>>,
+    mut chaser_query: Query<(&Position, &mut LinearVelocity), With<Minion>>,
+) {
+    let speed = balance.minion.speed * time.delta_seconds();
+
+    for (pos_chaser, mut linear_vel) in chaser_query.iter_mut() {
+        // chase the nearest enemy
+        let nearest = target_query.iter().min_by(|a, b| {
+            a.distance_squared(pos_chaser.0)
+                .total_cmp(&b.distance_squared(pos_chaser.0))
+        });
+
+        if let Some(pos_target) = nearest {
+            let direction = Vec2::normalize_or_zero(pos_target.0 - pos_chaser.0);
+            linear_vel.x += direction.x * speed;
+            linear_vel.y += direction.y * speed;
+        }
+    }
+}
+
+fn handle_collisions(
+    mut commands: Commands,
+    mut event_reader_collisions: EventReader<CollisionStarted>,
+    damage_done_query: Query<&DamageDone>,
+    mana_gem_query: Query<&ManaGem>,
+    projectile_query: Query<&Projectile>,
+    mut ew_damage_taken: EventWriter<DamageTakenEvent>,
+    mut ew_mana_gained: EventWriter<ManaGainedEvent>,
+) {
+    let mut spent_projectiles = Vec::new();
+
+    for CollisionStarted(entity1, entity2) in event_reader_collisions.read() {
+        // projectile collisions, in either order: hurt whatever was hit and disappear
+        let projectile_hit = [(*entity1, *entity2), (*entity2, *entity1)]
+            .into_iter()
+            .find(|(projectile, _)| projectile_query.contains(*projectile));
+        if let Some((projectile, target)) = projectile_hit {
+            if !spent_projectiles.contains(&projectile) {
+                spent_projectiles.push(projectile);
+                commands.entity(projectile).despawn_recursive();
+                ew_damage_taken.send(DamageTakenEvent {
+                    giver: projectile,
+                    receiver: target,
+                    amount: projectile_query.get(projectile).map_or(0, |p| p.damage),
+                });
+            }
+            continue;
+        }
+
+        // mana gem collisions
+        if let Ok(mana_gem) = mana_gem_query.get(*entity2) {
+            ew_mana_gained.send(ManaGainedEvent {
+                player: *entity1,
+                mana_gem: *entity2,
+                amount: mana_gem.0,
+            });
+        }
+
+        // damaging collisions
+        if let Ok(damage) = damage_done_query.get(*entity1) {
+            if damage.0 == 0 {
+                trace!("Ignoring a zero damage event");
+                continue;
+            }
+
+            debug!(
+                "Sending damage taken event from {:?} to {:?} for {} damage",
+                entity1, entity2, damage.0
+            );
+
+            ew_damage_taken.send(DamageTakenEvent {
+                giver: *entity1,
+                receiver: *entity2,
+                amount: damage.0,
+            });
+        }
+    }
+}
+
+fn burn_fuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<Balance>,
+    spatial_query: SpatialQuery,
+    mut fuse_query: Query<(Entity, &mut Fuse, &Position, &Name)>,
+    health_query: Query<(), With<Health>>,
+    tank_query: Query<(), With<Tank>>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut game_status: ResMut<GameStatus>,
+    mut ew_damage_taken: EventWriter<DamageTakenEvent>,
+    mut ew_explosion: EventWriter<ExplosionEvent>,
+    mut ew_play_sfx: EventWriter<PlaySfx>,
+    mut ew_spawn_particles: EventWriter<SpawnParticles>,
+    mut ew_shake_camera: EventWriter<ShakeCamera>,
+) {
+    let radius = balance.minion.blast_radius;
+    let damage = balance.minion.damage;
+
+    for (bomb, mut fuse, position, name) in fuse_query.iter_mut() {
+        if !fuse.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        info!("{} ({:?}) explodes at {}.", name, bomb, position.0);
+
+        // everything with health caught in the blast is hit, the player included
+        let hits = spatial_query.shape_intersections(
+            &Collider::circle(radius),
+            position.0,
+            0.0,
+            SpatialQueryFilter::default(),
+        );
+        if hits.iter().any(|entity| enemy_query.contains(*entity)) {
+            game_status.stats.record_bomb_hit();
+        }
+        for receiver in hits.into_iter().filter(|entity| health_query.contains(*entity)) {
+            let amount = if tank_query.contains(receiver) {
+                (damage as f32 * balance.tank.blast_damage_taken).round() as i32
+            } else {
+                damage
+            };
+            ew_damage_taken.send(DamageTakenEvent {
+                giver: bomb,
+                receiver,
+                amount,
+            });
+        }
+
+        ew_explosion.send(ExplosionEvent {
+            bomb,
+            position: position.0,
+            radius,
+        });
+
+        commands.entity(bomb).despawn_recursive();
+
+        ew_play_sfx.send(PlaySfx::at(SfxKind::Explosion, position.0));
+        ew_spawn_particles.send(SpawnParticles {
+            effect: ParticleEffect::Explosion,
+            position: position.0,
+        });
+        ew_shake_camera.send(ShakeCamera(ShakeKind::Explosion));
+    }
+}
+
+fn handle_damage_taken(
+    mut commands: Commands,
+    balance: Res<Balance>,
+    mut er_damage_taken: EventReader<DamageTakenEvent>,
+    mut health_query: Query<(&mut Health, &Name), Without<Invulnerable>>,
+    position_query: Query<&Position>,
+    transform_query: Query<&Transform>,
+    sprite_query: Query<(&SpriteAnimation, &Handle<Image>, &Sprite, &Transform)>,
+    player_query: Query<&Player>,
+    enemy_query: Query<&Enemy>,
+    mut ew_play_sfx: EventWriter<PlaySfx>,
+    mut ew_spawn_particles: EventWriter<SpawnParticles>,
+    mut ew_shake_camera: EventWriter<ShakeCamera>,
+    mut ew_spawn_popup: EventWriter<SpawnPopup>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    // entities turned invulnerable by an earlier event of this tick
+    let mut hurt_players = Vec::new();
+
+    for event in er_damage_taken.read() {
+        if hurt_players.contains(&event.receiver) {
+            continue;
+        }
+
+        if let Ok((mut health, name)) = health_query.get_mut(event.receiver) {
+            // already killed by an earlier event of this tick
+            if health.current == 0 {
+                continue;
+            }
+
+            // subtract the damage done, but do not go below zero
+            let before = health.current;
+            health.current = cmp::max(0, health.current - event.amount);
+            if enemy_query.contains(event.receiver) {
+                game_status.stats.record_damage((before - health.current) as u32);
+            } else if player_query.contains(event.receiver) && health.current < before {
+                game_status.stats.record_player_hit();
+            }
+
+            info!(
+                "{} ({:?}) takes {:?} damage from {:?} (final health = {:?})",
+                name, event.receiver, event.amount, event.giver, health.current,
+            );
+
+            if let Ok(position) = position_query.get(event.receiver) {
+                let kind = if player_query.contains(event.receiver) {
+                    PopupKind::PlayerDamage
+                } else {
+                    PopupKind::Damage
+                };
+                ew_spawn_popup.send(SpawnPopup {
+                    kind,
+                    amount: before - health.current,
+                    position: position.0,
+                });
+            }
+
+            // if the health is equal to zero, the event receiver dies
+            if health.current == 0 {
+                info!("{} ({:?}) dies.", name, event.receiver);
+                if let Ok((animation, texture, sprite, transform)) = sprite_query.get(event.receiver) {
+                    spawn_corpse(&mut commands, animation, texture.clone(), sprite, *transform);
+                }
+                commands.entity(event.receiver).despawn_recursive();
+
+                if let Ok(_player) = player_query.get(event.receiver) {
+                    ew_play_sfx.send(PlaySfx::new(SfxKind::PlayerDie));
+
+                    next_state.set(GameState::GameOver);
+                    game_status.result = GameResult::Lose;
+
+                } else if let Ok(_enemy) = enemy_query.get(event.receiver) {
+                    game_status.stats.record_kill();
+                    ew_shake_camera.send(ShakeCamera(ShakeKind::SlimeDeath));
+                    match transform_query.get(event.receiver) {
+                        Ok(transform) => {
+                            let position = transform.translation.truncate();
+                            ew_play_sfx.send(PlaySfx::at(SfxKind::EnemyDie, position));
+                            ew_spawn_particles.send(SpawnParticles {
+                                effect: ParticleEffect::SlimeDeath,
+                                position,
+                            });
+                        }
+                        Err(_) => {
+                            ew_play_sfx.send(PlaySfx::new(SfxKind::EnemyDie));
+                        }
+                    }
+                }
+            } else if player_query.contains(event.receiver) {
+                hurt_players.push(event.receiver);
+                commands.entity(event.receiver).insert(Invulnerable {
+                    timer: Timer::from_seconds(balance.player.invulnerability, TimerMode::Once),
+                });
+
+                ew_shake_camera.send(ShakeCamera(ShakeKind::PlayerHit));
+                if let Ok(position) = position_query.get(event.receiver) {
+                    ew_spawn_particles.send(SpawnParticles {
+                        effect: ParticleEffect::PlayerHurt,
+                        position: position.0,
+                    });
+                }
+
+                // push the player away from the attacker, if it is still around
+                if let Ok([attacker, receiver]) =
+                    position_query.get_many([event.giver, event.receiver])
+                {
+                    let away = Vec2::normalize_or_zero(receiver.0 - attacker.0);
+                    commands.entity(event.receiver).insert(Knockback {
+                        velocity: away * balance.player.knockback,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn apply_knockback(
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<Balance>,
+    mut knockback_query: Query<(Entity, &mut Knockback, &mut Position)>,
+) {
+    let radius = balance.player.radius;
+
+    for (entity, mut knockback, mut position) in knockback_query.iter_mut() {
+        let moved = position.0 + knockback.velocity * time.delta_seconds();
+        position.x = moved.x.clamp(-HALF_WIDTH + radius, HALF_WIDTH - radius);
+        position.y = moved.y.clamp(-HALF_HEIGHT + radius, HALF_HEIGHT - radius);
+
+        knockback.velocity *= (1.0 - 8.0 * time.delta_seconds()).max(0.0);
+        if knockback.velocity.length() < 1.0 {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
+
+// anything still touching the entity hurts it again, as no new collision would be started
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    collisions: Res<Collisions>,
+    mut invulnerable_query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+    damage_done_query: Query<&DamageDone>,
+    mut ew_damage_taken: EventWriter<DamageTakenEvent>,
+) {
+    for (entity, mut invulnerable, mut visibility) in invulnerable_query.iter_mut() {
+        if invulnerable.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+
+            for contacts in collisions.collisions_with_entity(entity) {
+                let other = if contacts.entity1 == entity {
+                    contacts.entity2
+                } else {
+                    contacts.entity1
+                };
+                if let Ok(damage) = damage_done_query.get(other) {
+                    if damage.0 > 0 && contacts.during_current_frame {
+                        ew_damage_taken.send(DamageTakenEvent {
+                            giver: other,
+                            receiver: entity,
+                            amount: damage.0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn update_invulnerability_blink(mut invulnerable_query: Query<(&Invulnerable, &mut Visibility)>) {
+    for (invulnerable, mut visibility) in invulnerable_query.iter_mut() {
+        *visibility = if (invulnerable.timer.elapsed_secs() * 10.0).fract() < 0.5 {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn split_slimes(
+    mut commands: Commands,
+    sprite_res: Res<SpriteAssets>,
+    balance: Res<Balance>,
+    slime_query: Query<(Entity, &Slime, &Health, &Position, &Name)>,
+) {
+    let split = &balance.enemy.split;
+
+    for (entity, slime, health, position, name) in slime_query.iter() {
+        let threshold = health.max as f32 * split.below_health;
+        if slime.generation >= split.generations || health.current as f32 >= threshold {
+            continue;
+        }
+
+        let generation = slime.generation + 1;
+        let child_health = cmp::max(1, health.current / 2);
+        let offset = balance.enemy.radius_at(generation) + 1.0;
+
+        info!(
+            "{} ({:?}) splits into two slimes with {} health each.",
+            name, entity, child_health
+        );
+
+        commands.entity(entity).despawn_recursive();
+        for side in [-1.0, 1.0] {
+            spawn_slime(
+                &mut commands,
+                &sprite_res,
+                &balance,
+                position.0 + Vector::new(side * offset, 0.0),
+                generation,
+                child_health,
+            );
+        }
+    }
+}
+
+// clearing the last wave wins the match, unless playing in endless mode
+fn advance_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    sprite_res: Res<SpriteAssets>,
+    balance: Res<Balance>,
+    game_mode: Res<GameMode>,
+    mut rng: ResMut<GameRng>,
+    mut waves: ResMut<WaveManager>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    if let Some(timer) = waves.intermission.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            waves.intermission = None;
+            waves.wave += 1;
+            spawn_wave(&mut commands, &sprite_res, &balance, &mut rng, waves.wave);
+        }
+        return;
+    }
+
+    if game_status.result != GameResult::None || !enemy_query.is_empty() {
+        return;
+    }
+
+    if balance.waves.is_last(waves.wave) && *game_mode == GameMode::Standard {
+        info!("All waves cleared.");
+        next_state.set(GameState::GameOver);
+        game_status.result = GameResult::Win;
+    } else {
+        info!("Wave {} cleared.", waves.wave + 1);
+        waves.intermission = Some(Timer::from_seconds(
+            balance.waves.intermission,
+            TimerMode::Once,
+        ));
+    }
+}
+
+fn update_wave_banner(
+    waves: Res<WaveManager>,
+    mut banner_query: Query<(&mut Text, &mut Visibility), With<WaveBanner>>,
+) {
+    for (mut text, mut visibility) in banner_query.iter_mut() {
+        match &waves.intermission {
+            Some(timer) => {
+                text.sections[0].value = format!(
+                    "Wave {} cleared!\nWave {} in {:.0}...",
+                    waves.wave + 1,
+                    waves.wave + 2,
+                    timer.remaining_secs().ceil(),
+                );
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn setup_mana_spawning(mut commands: Commands, balance: Res<Balance>) {
+    commands.insert_resource(ManaSpawnConfig {
+        timer: Timer::new(
+            Duration::from_secs_f32(balance.mana_gem.spawn_interval),
+            TimerMode::Repeating,
+        ),
+    })
+}
+
+fn mana_spawner(
+    mut commands: Commands,
+    sprite_res: Res<SpriteAssets>,
+    time: Res<Time>,
+    balance: Res<Balance>,
+    mut config: ResMut<ManaSpawnConfig>,
+    mut rng: ResMut<GameRng>,
+    mana_gem_query: Query<&ManaGem>,
+) {
+    // tick the timer
+    config.timer.tick(time.delta());
+
+    // if the timer has elapsed, spawn a gem
+    if config.timer.finished() && mana_gem_query.iter().len() <= 10 {
+        let gap = 5.0;
+        let gem_x = rng.gen_range(-HALF_WIDTH + gap..=HALF_WIDTH - gap);
+        let gem_y = rng.gen_range(-HALF_HEIGHT + gap..=HALF_HEIGHT - gap);
+        let gem_pos = Vector::new(gem_x, gem_y);
+
+        debug!("Spawning new mana gem at {}.", gem_pos);
+
+        commands
+            .spawn(ManaGem(balance.mana_gem.mana))
+            .insert(Name::new("ManaGem"))
+            .insert(RigidBody::Kinematic)
+            .insert(Collider::circle(balance.mana_gem.radius))
+            .insert(CollisionLayers::new(GameLayer::Gems, [GameLayer::Player]))
+            .insert(Position(gem_pos))
+            .insert(SpriteBundle {
+                texture: sprite_res.mana_gem.clone(),
+                ..default()
+            })
+            .insert(SpriteAnimation::new(sprite_res.mana_gem_animations.clone()))
+            .insert(InGameScreen);
+    }
+}
+
+fn handle_mana_gained(
+    mut commands: Commands,
+    mut er_mana_gained: EventReader<ManaGainedEvent>,
+    mut mana_query: Query<(&mut Mana, &Name), With<Mana>>,
+    transform_query: Query<&Transform>,
+    mut ew_play_sfx: EventWriter<PlaySfx>,
+    mut ew_spawn_particles: EventWriter<SpawnParticles>,
+    mut ew_spawn_popup: EventWriter<SpawnPopup>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    for event in er_mana_gained.read() {
+        if let Ok((mut mana, name)) = mana_query.get_mut(event.player) {
+            if mana.current < mana.max {
+                // de-spawn the mana gem
+                commands.entity(event.mana_gem).despawn();
+
+                match transform_query.get(event.mana_gem) {
+                    Ok(transform) => {
+                        let position = transform.translation.truncate();
+                        ew_play_sfx.send(PlaySfx::at(SfxKind::ManaGem, position));
+                        ew_spawn_particles.send(SpawnParticles {
+                            effect: ParticleEffect::GemCollect,
+                            position,
+                        });
+                    }
+                    Err(_) => {
+                        ew_play_sfx.send(PlaySfx::new(SfxKind::ManaGem));
+                    }
+                }
+
+                // add the event amount, but do not go over the maximum
+                let before = mana.current;
+                mana.current = cmp::min(mana.max, mana.current + event.amount);
+                game_status.stats.record_gem((mana.current - before) as u32);
+
+                info!(
+                    "{} ({:?}) gains {:?} mana (final mana total = {:?})",
+                    name, event.player, event.amount, mana.current,
+                );
+
+                if let Ok(transform) = transform_query.get(event.player) {
+                    ew_spawn_popup.send(SpawnPopup {
+                        kind: PopupKind::Mana,
+                        amount: mana.current - before,
+                        position: transform.translation.truncate(),
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_kira_audio::AudioChannel;
+use std::time::Duration;
+use crate::audio::{EffectsChannel, MusicChannel};
+use crate::balance::Balance;
+use crate::loading::*;
+use crate::settings::Settings;
+use crate::FontResource;
+
+pub const HEADLESS_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// the arena without a window, renderer or audio output, so a whole match can be stepped with
+// app.update()
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // plugins
+            .add_plugins(MinimalPlugins)
+            .add_plugins((TransformPlugin, HierarchyPlugin, InputPlugin))
+
+            // step the simulation by a fixed amount every update, regardless of wall time
+            .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_FRAME_TIME))
+
+            // stubbed resources, normally provided by the asset loader and audio plugin
+            .init_resource::<AudioAssets>()
+            .init_resource::<SpriteAssets>()
+            .init_resource::<FontResource>()
+            .init_resource::<AudioChannel<MusicChannel>>()
+            .init_resource::<AudioChannel<EffectsChannel>>()
+            .init_resource::<Settings>()
+            .insert_resource(Balance::bundled());
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::gameplay::{GameMode, GameStatus};
+use crate::replay::ReplayPlayback;
+use crate::rng::GameRng;
+use crate::storage::{load_ron, save_ron};
+use crate::GameState;
+
+pub const MAX_HIGH_SCORES: usize = 10;
+
+pub const MAX_NAME_LENGTH: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    // so the match can be played again with --seed
+    pub seed: u64,
+    pub mode: GameMode,
+    // YYYY-MM-DD, UTC
+    pub date: String,
+    pub time_survived: f32,
+}
+
+// highest first
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_none_or(|last| score > last.score))
+    }
+
+    // ties go below the score that was there first; returns None if the entry did not make it
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    // one entry per line, so a damaged file loses as little as possible
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        let mut text = String::from("[\n");
+        for entry in self.entries.iter() {
+            text.push_str("    ");
+            text.push_str(&ron::to_string(entry)?);
+            text.push_str(",\n");
+        }
+        text.push_str("]\n");
+        Ok(text)
+    }
+
+    // a damaged file keeps every line that still holds a valid entry
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        let entries = match ron::from_str::<Vec<HighScoreEntry>>(text) {
+            Ok(entries) => entries,
+            Err(error) => {
+                let salvaged: Vec<HighScoreEntry> = text
+                    .lines()
+                    .filter_map(|line| {
+                        let line = line.trim().trim_end_matches(',');
+                        ron::from_str(line).ok()
+                    })
+                    .collect();
+                if salvaged.is_empty() {
+                    return Err(error);
+                }
+                warn!("Recovered {} high scores from a damaged table: {}", salvaged.len(), error);
+                salvaged
+            }
+        };
+
+        let mut high_scores = Self::default();
+        for mut entry in entries {
+            entry.name = clean_name(&entry.name);
+            high_scores.insert(entry);
+        }
+        Ok(high_scores)
+    }
+}
+
+pub fn clean_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+pub fn today() -> String {
+    format_date(unix_seconds())
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn unix_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(target_family = "wasm")]
+fn unix_seconds() -> u64 {
+    // `SystemTime` is not available on the web
+    (web_sys::js_sys::Date::now() / 1000.0) as u64
+}
+
+pub fn format_date(unix_seconds: u64) -> String {
+    // civil-from-days conversion, counting in 400-year eras that start on March 1st
+    let days = (unix_seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct HighScoreStore {
+    pub path: Option<String>,
+}
+
+impl HighScoreStore {
+    pub fn load(&self) -> HighScores {
+        load_ron(self.path.as_deref(), "high scores", HighScores::from_ron)
+    }
+
+    pub fn save(&self, high_scores: &HighScores) {
+        save_ron(self.path.as_deref(), "high scores", || high_scores.to_ron())
+    }
+}
+
+// only present when the match that just ended makes it into the table
+#[derive(Resource, Debug, Clone)]
+pub struct PendingHighScore(pub HighScoreEntry);
+
+#[derive(Event, Debug, Clone)]
+pub struct SaveHighScore {
+    pub name: String,
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct LastHighScoreRank(pub usize);
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // resources
+            .init_resource::<HighScoreStore>()
+            .init_resource::<HighScores>()
+
+            // events
+            .add_event::<SaveHighScore>()
+
+            // startup systems
+            .add_systems(Startup, load_high_scores)
+
+            // on-enter / on-exit: game over
+            .add_systems(OnEnter(GameState::GameOver), prepare_high_score)
+            .add_systems(OnExit(GameState::GameOver), discard_pending_high_score)
+
+            // update systems
+            .add_systems(Update, save_high_score.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+fn load_high_scores(mut commands: Commands, store: Res<HighScoreStore>) {
+    commands.insert_resource(store.load());
+}
+
+pub fn prepare_high_score(
+    mut commands: Commands,
+    game_status: Res<GameStatus>,
+    game_mode: Res<GameMode>,
+    game_rng: Res<GameRng>,
+    high_scores: Res<HighScores>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    commands.remove_resource::<LastHighScoreRank>();
+
+    // a replay is a match that has already been played
+    if playback.is_some() || !high_scores.qualifies(game_status.stats.score) {
+        return;
+    }
+
+    commands.insert_resource(PendingHighScore(HighScoreEntry {
+        name: String::new(),
+        score: game_status.stats.score,
+        seed: game_rng.seed(),
+        mode: *game_mode,
+        date: today(),
+        time_survived: game_status.stats.time_survived,
+    }));
+}
+
+fn save_high_score(
+    mut commands: Commands,
+    mut er_save: EventReader<SaveHighScore>,
+    pending: Option<Res<PendingHighScore>>,
+    store: Res<HighScoreStore>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    let Some(event) = er_save.read().last() else {
+        return;
+    };
+    let Some(pending) = pending else {
+        return;
+    };
+
+    let mut entry = pending.0.clone();
+    entry.name = match clean_name(&event.name) {
+        name if name.is_empty() => "Anonymous".to_string(),
+        name => name,
+    };
+
+    if let Some(rank) = high_scores.insert(entry) {
+        store.save(&high_scores);
+        commands.insert_resource(LastHighScoreRank(rank));
+    }
+    commands.remove_resource::<PendingHighScore>();
+}
+
+fn discard_pending_high_score(mut commands: Commands) {
+    commands.remove_resource::<PendingHighScore>();
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_xpbd_2d::math::Vector;
+use bevy_xpbd_2d::plugins::sync::SyncConfig;
+use bevy_xpbd_2d::prelude::*;
+
+// blends the Transform of bodies simulated in FixedUpdate between the last two ticks
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // the interpolated transform must never be written back into the simulation
+            .insert_resource(SyncConfig {
+                position_to_transform: true,
+                transform_to_position: false,
+            })
+            .add_systems(FixedPostUpdate, store_tick_positions)
+            .add_systems(PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct InterpolatedPosition {
+    previous: Vector,
+    current: Vector,
+}
+
+impl InterpolatedPosition {
+    pub fn new(position: Vector) -> Self {
+        Self {
+            previous: position,
+            current: position,
+        }
+    }
+}
+
+fn store_tick_positions(mut query: Query<(&Position, &mut InterpolatedPosition)>) {
+    for (position, mut interpolated) in query.iter_mut() {
+        interpolated.previous = interpolated.current;
+        interpolated.current = position.0;
+    }
+}
+
+fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&InterpolatedPosition, &mut Transform)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    for (interpolated, mut transform) in query.iter_mut() {
+        let translation = interpolated.previous.lerp(interpolated.current, alpha);
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
@@ -0,0 +1,70 @@
+pub mod achievements;
+pub mod animation;
+pub mod args;
+pub mod balance;
+pub mod bars;
+pub mod bindings;
+pub mod camera;
+pub mod classes;
+pub mod audio;
+pub mod loading;
+pub mod gameplay;
+pub mod headless;
+pub mod highscores;
+pub mod interpolation;
+pub mod menus;
+pub mod particles;
+pub mod popups;
+pub mod music;
+pub mod replay;
+pub mod rng;
+pub mod ron_asset;
+pub mod settings;
+pub mod stats;
+pub mod storage;
+
+use bevy::prelude::*;
+
+pub const WINDOW_WIDTH: f32 = 768.0;
+pub const WINDOW_HEIGHT: f32 = 512.0;
+
+pub const HALF_HEIGHT: f32 = WINDOW_HEIGHT / 2.0;
+pub const HALF_WIDTH: f32 = WINDOW_WIDTH / 2.0;
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    AssetLoading,
+    MainMenu,
+    InGame,
+    GameOver,
+    HighScores,
+    Achievements,
+    // passed through for a single frame to restart a match from scratch
+    Restarting,
+}
+
+// only meaningful while in game
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// opened on top of the main or pause menu
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+    Controls,
+}
+
+#[derive(Component)]
+pub struct CameraMarker;
+
+#[derive(Resource, Debug, Default)]
+pub struct FontResource {
+    pub font: Handle<Font>,
+}
@@ -2,8 +2,9 @@ use bevy::asset::Handle;
 use bevy::prelude::{Image, Resource};
 use bevy_asset_loader::asset_collection::AssetCollection;
 use bevy_kira_audio::AudioSource;
+use crate::animation::AnimationSet;
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct AudioAssets {
     #[asset(path = "sounds/Action_-_Keep_Moving.ogg")]
     pub bgm: Handle<AudioSource>,
@@ -27,17 +28,41 @@ pub struct AudioAssets {
     pub oom: Handle<AudioSource>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct SpriteAssets {
     #[asset(path = "images/Sprite-Player.png")]
     pub player: Handle<Image>,
+
+    #[asset(path = "images/Sprite-Player.anim.ron")]
+    pub player_animations: Handle<AnimationSet>,
     
     #[asset(path = "images/Sprite-Enemy.png")]
     pub enemy: Handle<Image>,
+
+    #[asset(path = "images/Sprite-Enemy.anim.ron")]
+    pub enemy_animations: Handle<AnimationSet>,
+    
+    #[asset(path = "images/Sprite-Dasher.png")]
+    pub dasher: Handle<Image>,
+    
+    #[asset(path = "images/Sprite-RangedSlime.png")]
+    pub ranged_slime: Handle<Image>,
+    
+    #[asset(path = "images/Sprite-Tank.png")]
+    pub tank: Handle<Image>,
+    
+    #[asset(path = "images/Sprite-Projectile.png")]
+    pub projectile: Handle<Image>,
     
     #[asset(path = "images/Sprite-Bomb.png")]
     pub minion: Handle<Image>,
+
+    #[asset(path = "images/Sprite-Bomb.anim.ron")]
+    pub minion_animations: Handle<AnimationSet>,
     
     #[asset(path = "images/Sprite-ManaGem.png")]
     pub mana_gem: Handle<Image>,
+
+    #[asset(path = "images/Sprite-ManaGem.anim.ron")]
+    pub mana_gem_animations: Handle<AnimationSet>,
 }
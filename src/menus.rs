@@ -0,0 +1,920 @@
+use bevy::prelude::*;
+use bevy_ui_dsl::*;
+use crate::classes::*;
+use crate::achievements::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::ReceivedCharacter;
+use crate::bindings::*;
+use crate::gameplay::{GameMode, GameStatus, PlayerAction};
+use crate::highscores::*;
+use crate::settings::Settings;
+use crate::stats::format_duration;
+use crate::{GameState, PauseState, SettingsState};
+
+pub struct MenusPlugin;
+
+impl Plugin for MenusPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // on-enter
+            .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
+            .add_systems(OnEnter(GameState::HighScores), setup_high_scores_screen)
+            .add_systems(OnEnter(GameState::Achievements), setup_achievements_screen)
+            .add_systems(OnEnter(GameState::GameOver), setup_game_over.after(prepare_high_score))
+            .add_systems(OnEnter(PauseState::Paused), setup_pause_menu)
+            .add_systems(OnEnter(SettingsState::Open), setup_settings_screen)
+            .add_systems(OnEnter(SettingsState::Controls), setup_controls_screen)
+
+            // update systems
+            .add_systems(Update, (
+                handle_main_menu_actions.run_if(in_state(GameState::MainMenu)),
+                handle_high_scores_actions.run_if(in_state(GameState::HighScores)),
+                handle_achievements_actions.run_if(in_state(GameState::Achievements)),
+                (
+                    show_achievement_toasts,
+                    fade_toasts,
+                ).chain(),
+                (
+                    handle_game_over_actions,
+                    type_high_score_name,
+                    update_name_entry,
+                ).chain().run_if(in_state(GameState::GameOver)),
+                handle_pause_menu_actions.run_if(in_state(PauseState::Paused)),
+                (
+                    handle_settings_actions,
+                    drag_settings_sliders,
+                    update_settings_screen,
+                ).chain().run_if(in_state(SettingsState::Open)),
+                (
+                    handle_controls_actions,
+                    capture_binding,
+                    update_controls_screen,
+                ).chain().run_if(in_state(SettingsState::Controls)),
+            ))
+
+            // on exit
+            .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu)
+            .add_systems(OnExit(GameState::HighScores), cleanup_high_scores_screen)
+            .add_systems(OnExit(GameState::Achievements), cleanup_achievements_screen)
+            .add_systems(OnExit(GameState::GameOver), cleanup_game_over_screen)
+            .add_systems(OnExit(PauseState::Paused), (
+                cleanup_pause_menu,
+                close_settings,
+            ))
+            .add_systems(OnExit(SettingsState::Open), cleanup_settings_screen)
+            .add_systems(OnExit(SettingsState::Controls), cleanup_controls_screen);
+    }
+}
+
+#[derive(Component, PartialEq, Eq, Hash)]
+enum MainMenuScreen {
+    Node,
+    Text,
+    BeginButton,
+    EndlessButton,
+    ScoresButton,
+    AchievementsButton,
+    SettingsButton,
+}
+
+#[derive(Component, PartialEq, Eq, Hash)]
+enum AchievementsScreen {
+    Node,
+    Text,
+    BackButton,
+}
+
+#[derive(Component)]
+struct ToastArea;
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+}
+
+// seconds, the last of which is spent fading out
+const TOAST_DURATION: f32 = 4.0;
+
+#[derive(Component, PartialEq, Eq, Hash)]
+enum HighScoresScreen {
+    Node,
+    Text,
+    BackButton,
+}
+
+#[derive(Component, PartialEq, Eq, Hash)]
+enum PauseMenuScreen {
+    Node,
+    Text,
+    ResumeButton,
+    RestartButton,
+    SettingsButton,
+    QuitButton,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
+enum SettingsScreen {
+    Node,
+    Text,
+    Label(Setting),
+    Slider(Setting),
+    SliderFill(Setting),
+    Toggle(Setting),
+    ControlsButton,
+    BackButton,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Setting {
+    MusicVolume,
+    EffectsVolume,
+    ScreenShake,
+    Fullscreen,
+    Vsync,
+}
+
+impl Setting {
+    fn fraction(self, settings: &Settings) -> f32 {
+        match self {
+            Setting::MusicVolume => settings.music_volume as f32,
+            Setting::EffectsVolume => settings.effects_volume as f32,
+            Setting::ScreenShake => settings.screen_shake,
+            Setting::Fullscreen | Setting::Vsync => 0.0,
+        }
+    }
+
+    fn set_fraction(self, settings: &mut Settings, value: f32) {
+        match self {
+            Setting::MusicVolume => settings.music_volume = value as f64,
+            Setting::EffectsVolume => settings.effects_volume = value as f64,
+            Setting::ScreenShake => settings.screen_shake = value,
+            Setting::Fullscreen | Setting::Vsync => {}
+        }
+    }
+
+    fn toggle(self, settings: &mut Settings) {
+        match self {
+            Setting::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            _ => {}
+        }
+    }
+
+    fn label(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        match self {
+            Setting::MusicVolume => format!("Music: {:.0}%", settings.music_volume * 100.0),
+            Setting::EffectsVolume => format!("Effects: {:.0}%", settings.effects_volume * 100.0),
+            Setting::ScreenShake => format!("Screen shake: {:.0}%", settings.screen_shake * 100.0),
+            Setting::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            Setting::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
+enum ControlsScreen {
+    Node,
+    Text,
+    Bindings(PlayerAction),
+    ChangeButton(PlayerAction),
+    Message,
+    DefaultsButton,
+    BackButton,
+}
+
+#[derive(Resource, Default)]
+struct Rebinding {
+    action: Option<PlayerAction>,
+    // keys pressed so far for the four directions of movement
+    keys: Vec<KeyCode>,
+    message: String,
+}
+
+const MOVE_DIRECTIONS: [&str; 4] = ["up", "down", "left", "right"];
+
+#[derive(Component, PartialEq, Eq, Hash)]
+enum GameOverScreen {
+    Node,
+    Text,
+    NamePrompt,
+    Name,
+    SaveButton,
+    RestartButton,
+}
+
+#[derive(Resource, Default)]
+pub struct NameEntry {
+    pub name: String,
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    root(c_root, &assets, &mut commands, |p| {
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            texti("Bomb the slimes to survive!", c_text, c_pixel_title, MainMenuScreen::Text, p);
+        });
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            text_buttoni("Begin", c_button, c_pixel_button, MainMenuScreen::BeginButton, p);
+        });
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            text_buttoni("Endless", (c_button, c_menu_button), c_pixel_button, MainMenuScreen::EndlessButton, p);
+        });
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            text_buttoni("Scores", (c_button, c_menu_button), c_pixel_button, MainMenuScreen::ScoresButton, p);
+        });
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            text_buttoni("Awards", (c_button, c_menu_button), c_pixel_button, MainMenuScreen::AchievementsButton, p);
+        });
+        nodei(c_no_bg, MainMenuScreen::Node, p, |p| {
+            text_buttoni("Settings", (c_button, c_menu_button), c_pixel_button, MainMenuScreen::SettingsButton, p);
+        });
+    });
+}
+
+fn handle_main_menu_actions(
+    ui_entities: Query<(&MainMenuScreen, &Interaction), Changed<Interaction>>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *inter != Interaction::Pressed {
+            continue;
+        }
+
+        let mode = match id {
+            MainMenuScreen::BeginButton => GameMode::Standard,
+            MainMenuScreen::EndlessButton => GameMode::Endless,
+            MainMenuScreen::ScoresButton => {
+                next_state.set(GameState::HighScores);
+                break;
+            }
+            MainMenuScreen::AchievementsButton => {
+                next_state.set(GameState::Achievements);
+                break;
+            }
+            MainMenuScreen::SettingsButton => {
+                next_settings_state.set(SettingsState::Open);
+                break;
+            }
+            _ => continue,
+        };
+        *game_mode = mode;
+        next_state.set(GameState::InGame);
+        break;
+    }
+}
+
+fn cleanup_main_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<MainMenuScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_high_scores_screen(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+) {
+    root(c_root, &assets, &mut commands, |p| {
+        nodei(c_no_bg, HighScoresScreen::Node, p, |p| {
+            texti("High Scores", c_text, c_pixel_title, HighScoresScreen::Text, p);
+        });
+        nodei(c_column, HighScoresScreen::Node, p, |p| {
+            if high_scores.entries.is_empty() {
+                texti("No scores yet. Go bomb some slimes!", c_stat_line, c_pixel_button, HighScoresScreen::Text, p);
+            } else {
+                // the pixel font is monospaced, so padding lines up the columns
+                let header = format!(
+                    "{:>3} {:<12} {:>7} {:<8} {:>5} {:<10} {}",
+                    "#", "Name", "Score", "Mode", "Time", "Date", "Seed"
+                );
+                texti(header, c_stat_line, c_pixel_small, HighScoresScreen::Text, p);
+                for (rank, entry) in high_scores.entries.iter().enumerate() {
+                    let line = format!(
+                        "{:>3} {:<12} {:>7} {:<8} {:>5} {:<10} {}",
+                        format!("{}.", rank + 1),
+                        entry.name,
+                        entry.score,
+                        format!("{:?}", entry.mode),
+                        format_duration(entry.time_survived),
+                        entry.date,
+                        entry.seed
+                    );
+                    texti(line, c_stat_line, c_pixel_small, HighScoresScreen::Text, p);
+                }
+            }
+        });
+        nodei(c_no_bg, HighScoresScreen::Node, p, |p| {
+            text_buttoni("Back", (c_button, c_menu_button), c_pixel_button, HighScoresScreen::BackButton, p);
+        });
+    });
+}
+
+fn handle_high_scores_actions(
+    ui_entities: Query<(&HighScoresScreen, &Interaction), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *id == HighScoresScreen::BackButton && *inter == Interaction::Pressed {
+            next_state.set(GameState::MainMenu);
+            break;
+        }
+    }
+}
+
+fn cleanup_high_scores_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<HighScoresScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_achievements_screen(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    achievements: Res<UnlockedAchievements>,
+) {
+    root(c_root, &assets, &mut commands, |p| {
+        nodei(c_no_bg, AchievementsScreen::Node, p, |p| {
+            let title = format!("Achievements {}/{}", achievements.unlocked.len(), ACHIEVEMENTS.len());
+            texti(title, c_text, c_pixel_title, AchievementsScreen::Text, p);
+        });
+        for achievement in ACHIEVEMENTS.iter() {
+            nodei(c_achievement, AchievementsScreen::Node, p, |p| {
+                match achievements.get(achievement.id) {
+                    Some(unlocked) => {
+                        texti(achievement.name, (), c_pixel_button, AchievementsScreen::Text, p);
+                        let line = format!("{} - unlocked {}", achievement.description, unlocked.date);
+                        texti(line, (), c_pixel_small, AchievementsScreen::Text, p);
+                    }
+                    None => {
+                        texti(achievement.name, (), (c_pixel_button, c_locked), AchievementsScreen::Text, p);
+                        texti(achievement.description, (), (c_pixel_small, c_locked), AchievementsScreen::Text, p);
+                    }
+                }
+            });
+        }
+        nodei(c_no_bg, AchievementsScreen::Node, p, |p| {
+            text_buttoni("Back", (c_button, c_menu_button), c_pixel_button, AchievementsScreen::BackButton, p);
+        });
+    });
+}
+
+fn handle_achievements_actions(
+    ui_entities: Query<(&AchievementsScreen, &Interaction), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *id == AchievementsScreen::BackButton && *inter == Interaction::Pressed {
+            next_state.set(GameState::MainMenu);
+            break;
+        }
+    }
+}
+
+fn cleanup_achievements_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<AchievementsScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// toasts outlive the screen they were unlocked on
+fn show_achievement_toasts(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut er_unlocked: EventReader<AchievementUnlocked>,
+    area_query: Query<Entity, With<ToastArea>>,
+) {
+    if er_unlocked.is_empty() {
+        return;
+    }
+
+    let area = match area_query.get_single() {
+        Ok(area) => area,
+        Err(_) => rooti(c_toast_area, &assets, &mut commands, ToastArea, |_| {}),
+    };
+    for AchievementUnlocked(achievement) in er_unlocked.read() {
+        let toast = Toast {
+            timer: Timer::from_seconds(TOAST_DURATION, TimerMode::Once),
+        };
+        blanki(area, c_toast, &assets, &mut commands, toast, |p| {
+            texti("Achievement unlocked!", (), c_pixel_small, (), p);
+            texti(achievement.name, (), c_pixel_button, (), p);
+        });
+    }
+}
+
+// real time, so toasts still go away while the match is paused
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toast_query: Query<(Entity, &mut Toast, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut toast, mut background, children) in toast_query.iter_mut() {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = toast.timer.remaining_secs().min(1.0);
+        background.0.set_a(0.9 * alpha);
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+fn setup_game_over(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    game_status: ResMut<GameStatus>,
+    pending: Option<Res<PendingHighScore>>,
+) {
+    let stats = &game_status.stats;
+    let breakdown = [
+        format!("Score: {}", stats.score),
+        format!("Time survived: {}", format_duration(stats.time_survived)),
+        format!(
+            "Bombs: {} spawned, {} hit ({:.0}%)",
+            stats.bombs_spawned,
+            stats.bombs_hit,
+            stats.accuracy() * 100.0
+        ),
+        format!("Enemies killed: {}", stats.enemies_killed),
+        format!("Damage dealt: {}", stats.damage_dealt),
+        format!("Gems collected: {}", stats.gems_collected),
+        format!("Best combo: {} in a row", stats.best_combo),
+    ];
+
+    root(c_root, &assets, &mut commands, |p| {
+        nodei(c_no_bg, GameOverScreen::Node, p, |p| {
+            texti(format!("Game over! You {:?}!", game_status.result), c_text, c_pixel_title, GameOverScreen::Text, p);
+        });
+        nodei(c_column, GameOverScreen::Node, p, |p| {
+            for line in breakdown {
+                texti(line, c_stat_line, c_pixel_button, GameOverScreen::Text, p);
+            }
+        });
+        if pending.is_some() {
+            nodei(c_column, GameOverScreen::Node, p, |p| {
+                texti("New high score! Type your name:", c_stat_line, c_pixel_button, GameOverScreen::NamePrompt, p);
+                nodei(c_row, GameOverScreen::Node, p, |p| {
+                    texti("_", c_binding_label, c_pixel_button, GameOverScreen::Name, p);
+                    text_buttoni("Save", (c_button, c_menu_button), c_pixel_button, GameOverScreen::SaveButton, p);
+                });
+            });
+        }
+        nodei(c_no_bg, GameOverScreen::Node, p, |p| {
+            text_buttoni("Restart", c_button, c_pixel_button, GameOverScreen::RestartButton, p);
+        });
+    });
+
+    if pending.is_some() {
+        commands.init_resource::<NameEntry>();
+    }
+}
+
+fn handle_game_over_actions(
+    ui_entities: Query<(&GameOverScreen, &Interaction), Changed<Interaction>>,
+    name_entry: Option<Res<NameEntry>>,
+    mut ew_save: EventWriter<SaveHighScore>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *inter != Interaction::Pressed {
+            continue;
+        }
+
+        match id {
+            GameOverScreen::SaveButton => {
+                if let Some(name_entry) = &name_entry {
+                    ew_save.send(SaveHighScore {
+                        name: name_entry.name.clone(),
+                    });
+                }
+            }
+            GameOverScreen::RestartButton => next_state.set(GameState::InGame),
+            _ => continue,
+        }
+        break;
+    }
+}
+
+fn type_high_score_name(
+    mut er_characters: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    name_entry: Option<ResMut<NameEntry>>,
+    pending: Option<Res<PendingHighScore>>,
+    mut ew_save: EventWriter<SaveHighScore>,
+) {
+    let (Some(mut name_entry), Some(_)) = (name_entry, pending) else {
+        er_characters.clear();
+        return;
+    };
+
+    for event in er_characters.read() {
+        for c in event.char.chars().filter(|c| !c.is_control()) {
+            if name_entry.name.chars().count() < MAX_NAME_LENGTH {
+                name_entry.name.push(c);
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::Backspace) {
+        name_entry.name.pop();
+    }
+    if keys.just_pressed(KeyCode::Enter) || keys.just_pressed(KeyCode::NumpadEnter) {
+        ew_save.send(SaveHighScore {
+            name: name_entry.name.clone(),
+        });
+    }
+}
+
+fn update_name_entry(
+    name_entry: Option<Res<NameEntry>>,
+    pending: Option<Res<PendingHighScore>>,
+    rank: Option<Res<LastHighScoreRank>>,
+    mut text_query: Query<(&GameOverScreen, &mut Text)>,
+    mut button_query: Query<(&GameOverScreen, &mut Visibility), With<Button>>,
+) {
+    let Some(name_entry) = name_entry else {
+        return;
+    };
+
+    for (id, mut text) in text_query.iter_mut() {
+        let value = match (id, &rank) {
+            (GameOverScreen::NamePrompt, Some(rank)) => format!("Saved as #{} in the high scores!", rank.0 + 1),
+            (GameOverScreen::Name, Some(_)) => name_entry.name.clone(),
+            (GameOverScreen::Name, None) => format!("{}_", name_entry.name),
+            _ => continue,
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+
+    // nothing left to save once the entry is in the table
+    if pending.is_none() {
+        for (id, mut visibility) in button_query.iter_mut() {
+            if *id == GameOverScreen::SaveButton && *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+fn cleanup_game_over_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<GameOverScreen>>,
+) {
+    commands.remove_resource::<NameEntry>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    // the root dims the arena, so it has to be cleaned up as well
+    rooti((c_root, c_dim), &assets, &mut commands, PauseMenuScreen::Node, |p| {
+        nodei(c_no_bg, PauseMenuScreen::Node, p, |p| {
+            texti("Paused", c_text, c_pixel_title, PauseMenuScreen::Text, p);
+        });
+        nodei(c_no_bg, PauseMenuScreen::Node, p, |p| {
+            text_buttoni("Resume", (c_button, c_menu_button, c_wide_button), c_pixel_button, PauseMenuScreen::ResumeButton, p);
+        });
+        nodei(c_no_bg, PauseMenuScreen::Node, p, |p| {
+            text_buttoni("Restart", (c_button, c_menu_button, c_wide_button), c_pixel_button, PauseMenuScreen::RestartButton, p);
+        });
+        nodei(c_no_bg, PauseMenuScreen::Node, p, |p| {
+            text_buttoni("Settings", (c_button, c_menu_button, c_wide_button), c_pixel_button, PauseMenuScreen::SettingsButton, p);
+        });
+        nodei(c_no_bg, PauseMenuScreen::Node, p, |p| {
+            text_buttoni("Quit to Main Menu", (c_button, c_menu_button, c_wide_button), c_pixel_button, PauseMenuScreen::QuitButton, p);
+        });
+    });
+}
+
+fn handle_pause_menu_actions(
+    ui_entities: Query<(&PauseMenuScreen, &Interaction), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *inter != Interaction::Pressed {
+            continue;
+        }
+
+        match id {
+            PauseMenuScreen::ResumeButton => {}
+            PauseMenuScreen::RestartButton => next_state.set(GameState::Restarting),
+            PauseMenuScreen::QuitButton => next_state.set(GameState::MainMenu),
+            PauseMenuScreen::SettingsButton => {
+                next_settings_state.set(SettingsState::Open);
+                break;
+            }
+            _ => continue,
+        }
+        next_pause_state.set(PauseState::Running);
+        break;
+    }
+}
+
+fn cleanup_pause_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<PauseMenuScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_settings_screen(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    rooti((c_root, c_overlay), &assets, &mut commands, SettingsScreen::Node, |p| {
+        texti("Settings", c_text, c_pixel_title, SettingsScreen::Text, p);
+        for setting in [Setting::MusicVolume, Setting::EffectsVolume, Setting::ScreenShake] {
+            nodei(c_row, SettingsScreen::Node, p, |p| {
+                texti(setting.label(&settings), c_setting_label, c_pixel_button, SettingsScreen::Label(setting), p);
+                buttoni(c_slider, (SettingsScreen::Slider(setting), RelativeCursorPosition::default()), p, |p| {
+                    nodei(c_slider_fill, SettingsScreen::SliderFill(setting), p, |_| {});
+                });
+            });
+        }
+        for setting in [Setting::Fullscreen, Setting::Vsync] {
+            nodei(c_no_bg, SettingsScreen::Node, p, |p| {
+                buttoni((c_button, c_menu_button, c_wide_button), SettingsScreen::Toggle(setting), p, |p| {
+                    texti(setting.label(&settings), (), c_pixel_button, SettingsScreen::Label(setting), p);
+                });
+            });
+        }
+        nodei(c_row, SettingsScreen::Node, p, |p| {
+            text_buttoni("Controls", (c_button, c_menu_button, c_wide_button), c_pixel_button, SettingsScreen::ControlsButton, p);
+            text_buttoni("Back", (c_button, c_menu_button), c_pixel_button, SettingsScreen::BackButton, p);
+        });
+    });
+}
+
+fn handle_settings_actions(
+    ui_entities: Query<(&SettingsScreen, &Interaction), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *inter != Interaction::Pressed {
+            continue;
+        }
+
+        match id {
+            SettingsScreen::Toggle(setting) => setting.toggle(&mut settings),
+            SettingsScreen::ControlsButton => next_settings_state.set(SettingsState::Controls),
+            SettingsScreen::BackButton => next_settings_state.set(SettingsState::Closed),
+            _ => {}
+        }
+    }
+}
+
+// in steps of 5%
+fn drag_settings_sliders(
+    slider_query: Query<(&SettingsScreen, &Interaction, &RelativeCursorPosition)>,
+    mut settings: ResMut<Settings>,
+) {
+    for (id, inter, cursor) in &slider_query {
+        let (SettingsScreen::Slider(setting), Interaction::Pressed) = (id, inter) else {
+            continue;
+        };
+
+        if let Some(position) = cursor.normalized {
+            let value = (position.x.clamp(0.0, 1.0) * 20.0).round() / 20.0;
+            if setting.fraction(&settings) != value {
+                setting.set_fraction(&mut settings, value);
+            }
+        }
+    }
+}
+
+fn update_settings_screen(
+    settings: Res<Settings>,
+    mut label_query: Query<(&SettingsScreen, &mut Text)>,
+    mut fill_query: Query<(&SettingsScreen, &mut Style)>,
+) {
+    for (id, mut text) in label_query.iter_mut() {
+        if let SettingsScreen::Label(setting) = id {
+            let label = setting.label(&settings);
+            if text.sections[0].value != label {
+                text.sections[0].value = label;
+            }
+        }
+    }
+    for (id, mut style) in fill_query.iter_mut() {
+        if let SettingsScreen::SliderFill(setting) = id {
+            let width = Val::Percent(setting.fraction(&settings) * 100.0);
+            if style.width != width {
+                style.width = width;
+            }
+        }
+    }
+}
+
+fn close_settings(mut next_settings_state: ResMut<NextState<SettingsState>>) {
+    next_settings_state.set(SettingsState::Closed);
+}
+
+fn cleanup_settings_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<SettingsScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn setup_controls_screen(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    commands.init_resource::<Rebinding>();
+
+    rooti((c_root, c_overlay), &assets, &mut commands, ControlsScreen::Node, |p| {
+        texti("Controls", c_text, c_pixel_title, ControlsScreen::Text, p);
+        for action in PlayerAction::ALL {
+            nodei(c_row, ControlsScreen::Node, p, |p| {
+                texti(action_name(action), c_action_label, c_pixel_button, ControlsScreen::Text, p);
+                texti(describe_bindings(&settings.bindings, action), c_binding_label, c_pixel_button, ControlsScreen::Bindings(action), p);
+                text_buttoni("Change", (c_button, c_menu_button), c_pixel_button, ControlsScreen::ChangeButton(action), p);
+            });
+        }
+        texti("", c_text, c_pixel_button, ControlsScreen::Message, p);
+        nodei(c_row, ControlsScreen::Node, p, |p| {
+            text_buttoni("Reset to Defaults", (c_button, c_menu_button, c_wide_button), c_pixel_button, ControlsScreen::DefaultsButton, p);
+            text_buttoni("Back", (c_button, c_menu_button), c_pixel_button, ControlsScreen::BackButton, p);
+        });
+    });
+}
+
+fn handle_controls_actions(
+    ui_entities: Query<(&ControlsScreen, &Interaction), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for (id, inter) in &ui_entities {
+        if *inter != Interaction::Pressed {
+            continue;
+        }
+
+        match id {
+            ControlsScreen::ChangeButton(action) => {
+                *rebinding = Rebinding {
+                    action: Some(*action),
+                    keys: Vec::new(),
+                    message: rebinding_prompt(*action, 0),
+                };
+            }
+            ControlsScreen::DefaultsButton => {
+                settings.bindings = PlayerAction::default_input_map();
+                *rebinding = Rebinding {
+                    message: "Controls reset to defaults".to_string(),
+                    ..default()
+                };
+            }
+            ControlsScreen::BackButton => next_settings_state.set(SettingsState::Open),
+            _ => {}
+        }
+    }
+}
+
+fn rebinding_prompt(action: PlayerAction, direction: usize) -> String {
+    match action {
+        PlayerAction::Move => format!("Press the key for moving {}", MOVE_DIRECTIONS[direction]),
+        _ => format!("Press a key or gamepad button for {}", action_name(action)),
+    }
+}
+
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+
+    let pressed = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| CapturedInput::Key(*key))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| CapturedInput::Gamepad(button.button_type))
+        });
+    let Some(input) = pressed else {
+        return;
+    };
+
+    if action == PlayerAction::Move {
+        // movement is rebound one direction at a time, from the keyboard only
+        let CapturedInput::Key(key) = input else {
+            rebinding.message = "Movement can only be bound to keys".to_string();
+            return;
+        };
+        if rebinding.keys.contains(&key) {
+            rebinding.message = format!("{} is already used for another direction", key_name(key));
+            return;
+        }
+        if let Some(other) = conflicting_action(&settings.bindings, action, input) {
+            rebinding.message = format!("{} is already bound to {}", key_name(key), action_name(other));
+            return;
+        }
+
+        rebinding.keys.push(key);
+        if rebinding.keys.len() < MOVE_DIRECTIONS.len() {
+            rebinding.message = rebinding_prompt(action, rebinding.keys.len());
+            return;
+        }
+
+        let keys = [rebinding.keys[0], rebinding.keys[1], rebinding.keys[2], rebinding.keys[3]];
+        let result = rebind_movement(&mut settings.bindings, keys);
+        finish_rebinding(&mut rebinding, action, result);
+    } else {
+        let result = rebind_button(&mut settings.bindings, action, input);
+        finish_rebinding(&mut rebinding, action, result);
+    }
+}
+
+fn finish_rebinding(
+    rebinding: &mut Rebinding,
+    action: PlayerAction,
+    result: Result<(), PlayerAction>,
+) {
+    match result {
+        Ok(()) => {
+            *rebinding = Rebinding {
+                message: format!("{} rebound", action_name(action)),
+                ..default()
+            };
+        }
+        Err(other) => {
+            // keep listening for another input
+            rebinding.keys.clear();
+            rebinding.message = format!(
+                "Already bound to {}. {}",
+                action_name(other),
+                rebinding_prompt(action, 0)
+            );
+        }
+    }
+}
+
+fn update_controls_screen(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    mut text_query: Query<(&ControlsScreen, &mut Text)>,
+) {
+    for (id, mut text) in text_query.iter_mut() {
+        let value = match id {
+            ControlsScreen::Bindings(action) => describe_bindings(&settings.bindings, *action),
+            ControlsScreen::Message => rebinding.message.clone(),
+            _ => continue,
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn cleanup_controls_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<ControlsScreen>>,
+) {
+    commands.remove_resource::<Rebinding>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use bevy_xpbd_2d::prelude::Position;
+use std::time::Duration;
+use crate::audio::{music_volume, MusicChannel, MusicDucking};
+use crate::gameplay::{Enemy, Health, Player};
+use crate::loading::AudioAssets;
+use crate::settings::Settings;
+use crate::{GameState, PauseState};
+
+const CROSSFADE: f32 = 1.5;
+
+// the nearest enemy starts raising the intensity once it is closer than CALM_DISTANCE and maxes it
+// out at DANGER_DISTANCE
+const CALM_DISTANCE: f32 = 300.0;
+const DANGER_DISTANCE: f32 = 80.0;
+
+// health fraction below which the intensity rises
+const LOW_HEALTH: f32 = 0.5;
+
+// share of the music volume combat plays at while nothing is happening; full intensity plays it at 1
+const CALM_LEVEL: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTrack {
+    Menu,
+    Combat,
+    GameOver,
+}
+
+impl MusicTrack {
+    pub fn for_state(state: &GameState) -> Option<Self> {
+        match state {
+            GameState::AssetLoading => None,
+            GameState::InGame | GameState::Restarting => Some(Self::Combat),
+            GameState::GameOver => Some(Self::GameOver),
+            _ => Some(Self::Menu),
+        }
+    }
+
+    // the game ships a single 90 second song, so every track is a section of it; a track with a
+    // file of its own only needs its handle in AudioAssets
+    fn source(self, audio_assets: &AudioAssets) -> Handle<AudioSource> {
+        audio_assets.bgm.clone()
+    }
+
+    fn start(self) -> f64 {
+        match self {
+            Self::Menu => 0.0,
+            Self::Combat => 30.0,
+            Self::GameOver => 60.0,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct MusicDirector {
+    pub track: Option<MusicTrack>,
+    pub instance: Option<Handle<AudioInstance>>,
+    // 0 while calm, 1 with an enemy right next to the player or their health nearly gone
+    pub intensity: f32,
+}
+
+impl MusicDirector {
+    // share of the music volume the current track plays at
+    pub fn level(&self) -> f64 {
+        match self.track {
+            Some(MusicTrack::Combat) => CALM_LEVEL + (1.0 - CALM_LEVEL) * self.intensity as f64,
+            Some(MusicTrack::GameOver) => 0.5,
+            Some(MusicTrack::Menu) => 0.8,
+            None => 1.0,
+        }
+    }
+}
+
+pub fn combat_intensity(nearest_enemy: Option<f32>, health_fraction: f32) -> f32 {
+    let closeness = nearest_enemy.map_or(0.0, |distance| {
+        ((CALM_DISTANCE - distance) / (CALM_DISTANCE - DANGER_DISTANCE)).clamp(0.0, 1.0)
+    });
+    let danger = ((LOW_HEALTH - health_fraction) / LOW_HEALTH).clamp(0.0, 1.0);
+    closeness.max(danger)
+}
+
+pub struct MusicDirectorPlugin;
+
+impl Plugin for MusicDirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // resources
+            .init_resource::<MusicDirector>()
+
+            // on-enter / on-exit: paused
+            .add_systems(OnEnter(PauseState::Paused), pause_music)
+            .add_systems(OnExit(PauseState::Paused), resume_music)
+
+            // on-exit: in game
+            .add_systems(OnExit(GameState::InGame), calm_music)
+
+            // update systems
+            .add_systems(Update, (
+                crossfade_music,
+                follow_intensity.run_if(in_state(GameState::InGame)),
+            ));
+    }
+}
+
+fn crossfade_music(
+    game_state: Res<State<GameState>>,
+    mut director: ResMut<MusicDirector>,
+    settings: Res<Settings>,
+    ducking: Res<MusicDucking>,
+    audio_assets: Option<Res<AudioAssets>>,
+    music_channel: Res<AudioChannel<MusicChannel>>,
+    audio_instances: Option<ResMut<Assets<AudioInstance>>>,
+) {
+    let track = MusicTrack::for_state(game_state.get());
+    if track == director.track {
+        return;
+    }
+    let (Some(track), Some(audio_assets)) = (track, audio_assets) else {
+        return;
+    };
+
+    let fade = AudioTween::linear(Duration::from_secs_f32(CROSSFADE));
+    if let (Some(previous), Some(mut audio_instances)) = (director.instance.take(), audio_instances) {
+        if let Some(instance) = audio_instances.get_mut(&previous) {
+            instance.stop(fade.clone());
+        }
+    }
+
+    director.track = Some(track);
+    director.instance = Some(
+        music_channel
+            .play(track.source(&audio_assets))
+            .start_from(track.start())
+            .looped()
+            .with_volume(music_volume(&settings, &ducking, director.level()))
+            .fade_in(fade)
+            .handle(),
+    );
+}
+
+fn follow_intensity(
+    mut director: ResMut<MusicDirector>,
+    player_query: Query<(&Position, &Health), With<Player>>,
+    enemy_query: Query<&Position, With<Enemy>>,
+) {
+    let Ok((player_position, health)) = player_query.get_single() else {
+        return;
+    };
+
+    let nearest_enemy = enemy_query
+        .iter()
+        .map(|position| position.0.distance(player_position.0))
+        .reduce(f32::min);
+    let health_fraction = health.current.max(0) as f32 / health.max as f32;
+
+    // steps of a tenth, so the volume is not faded to a new value every frame
+    let intensity = (combat_intensity(nearest_enemy, health_fraction) * 10.0).round() / 10.0;
+    if director.intensity != intensity {
+        director.intensity = intensity;
+    }
+}
+
+fn calm_music(mut director: ResMut<MusicDirector>) {
+    director.intensity = 0.0;
+}
+
+fn pause_music(music_channel: Res<AudioChannel<MusicChannel>>) {
+    music_channel
+        .pause()
+        .fade_out(AudioTween::linear(Duration::from_millis(300)));
+}
+
+fn resume_music(music_channel: Res<AudioChannel<MusicChannel>>) {
+    music_channel
+        .resume()
+        .fade_in(AudioTween::linear(Duration::from_millis(300)));
+}
@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+use crate::animation::sample;
+use crate::ron_asset::*;
+use crate::GameState;
+
+// bursts beyond it are cut short
+pub const MAX_PARTICLES: usize = 512;
+
+// above every sprite of the arena
+const PARTICLE_Z: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleEffect {
+    Explosion,
+    GemCollect,
+    PlayerHurt,
+    SlimeDeath,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EmitterSpec {
+    pub count: u32,
+    // seconds
+    pub lifetime: (f32, f32),
+    // pixels per second
+    pub speed: (f32, f32),
+    // share of its velocity a particle loses every second
+    pub drag: f32,
+    pub size: f32,
+    // keyframes are spread evenly over the lifetime
+    pub scale: Vec<f32>,
+    pub color: Vec<(f32, f32, f32, f32)>,
+}
+
+impl EmitterSpec {
+    pub fn scale_at(&self, progress: f32) -> f32 {
+        sample(&self.scale, progress, |a, b, t| a + (b - a) * t).unwrap_or(1.0)
+    }
+
+    pub fn color_at(&self, progress: f32) -> Color {
+        sample(&self.color, progress, |(r0, g0, b0, a0), (r1, g1, b1, a1), t| {
+            Vec4::new(r0, g0, b0, a0).lerp(Vec4::new(r1, g1, b1, a1), t).into()
+        })
+        .map_or(Color::WHITE, |(r, g, b, a)| Color::rgba(r, g, b, a))
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParticleEffects {
+    pub explosion: EmitterSpec,
+    pub gem_collect: EmitterSpec,
+    pub player_hurt: EmitterSpec,
+    pub slime_death: EmitterSpec,
+}
+
+impl ParticleEffects {
+    pub fn from_ron(text: &str) -> Result<Self, RonAssetError> {
+        parse_ron(text)
+    }
+
+    // for apps that do not run the asset loader, like the headless tests
+    pub fn bundled() -> Self {
+        Self::from_ron(include_str!("../assets/game.particles.ron"))
+            .expect("the bundled particles file is valid")
+    }
+
+    pub fn emitter(&self, effect: ParticleEffect) -> &EmitterSpec {
+        match effect {
+            ParticleEffect::Explosion => &self.explosion,
+            ParticleEffect::GemCollect => &self.gem_collect,
+            ParticleEffect::PlayerHurt => &self.player_hurt,
+            ParticleEffect::SlimeDeath => &self.slime_death,
+        }
+    }
+}
+
+impl Validate for ParticleEffects {
+    fn validate(&self) -> Result<(), RonAssetError> {
+        let mut problems = Vec::new();
+        let emitters = [
+            ("explosion", &self.explosion),
+            ("gem_collect", &self.gem_collect),
+            ("player_hurt", &self.player_hurt),
+            ("slime_death", &self.slime_death),
+        ];
+        for (name, emitter) in emitters {
+            let (shortest, longest) = emitter.lifetime;
+            if !(shortest > 0.0 && shortest <= longest) {
+                problems.push(format!("{}.lifetime must be positive and in order", name));
+            }
+            let (slowest, fastest) = emitter.speed;
+            if !(slowest >= 0.0 && slowest <= fastest) {
+                problems.push(format!("{}.speed must not be negative and in order", name));
+            }
+            if !(emitter.drag >= 0.0 && emitter.size > 0.0) {
+                problems.push(format!("{}.drag must not be negative and size must be positive", name));
+            }
+        }
+
+        RonAssetError::check(problems)
+    }
+}
+
+impl Default for ParticleEffects {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct SpawnParticles {
+    pub effect: ParticleEffect,
+    pub position: Vec2,
+}
+
+// hidden particles are dead and wait in the ParticlePool
+#[derive(Component, Debug)]
+pub struct Particle {
+    pub effect: ParticleEffect,
+    pub velocity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+// so bursts do not spawn and despawn entities
+#[derive(Resource, Debug, Default)]
+pub struct ParticlePool {
+    pub free: Vec<Entity>,
+    pub spawned: usize,
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // assets
+            .init_asset::<ParticleEffects>()
+            .register_asset_loader(RonAssetLoader::<ParticleEffects>::new(&["particles.ron"]))
+
+            // events
+            .add_event::<SpawnParticles>()
+
+            // resources
+            .init_resource::<ParticleEffects>()
+            .init_resource::<ParticlePool>()
+
+            // startup systems
+            .add_systems(Startup, load_ron_file::<ParticleEffects>("game.particles.ron"))
+
+            // on-exit: asset loading
+            .add_systems(OnExit(GameState::AssetLoading), warn_if_not_loaded::<ParticleEffects>)
+
+            // on-exit: in game
+            .add_systems(OnExit(GameState::InGame), clear_particles)
+
+            // update systems
+            .add_systems(Update, (
+                apply_loaded::<ParticleEffects>,
+                update_particles,
+                emit_particles,
+            ).chain());
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    mut er_spawn_particles: EventReader<SpawnParticles>,
+    effects: Res<ParticleEffects>,
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(&mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    // particles are cosmetic, so they must not draw from the gameplay rng
+    let mut rng = rand::thread_rng();
+
+    for event in er_spawn_particles.read() {
+        let emitter = effects.emitter(event.effect);
+        for _ in 0..emitter.count {
+            let angle = rng.gen_range(0.0..TAU);
+            let particle = Particle {
+                effect: event.effect,
+                velocity: Vec2::from_angle(angle) * rng.gen_range(emitter.speed.0..=emitter.speed.1),
+                age: 0.0,
+                lifetime: rng.gen_range(emitter.lifetime.0..=emitter.lifetime.1),
+            };
+            let translation = event.position.extend(PARTICLE_Z);
+            let sprite = Sprite {
+                color: emitter.color_at(0.0),
+                custom_size: Some(Vec2::splat(emitter.size * emitter.scale_at(0.0))),
+                ..default()
+            };
+
+            if let Some(entity) = pool.free.pop() {
+                if let Ok((mut old, mut transform, mut old_sprite, mut visibility)) =
+                    particle_query.get_mut(entity)
+                {
+                    *old = particle;
+                    transform.translation = translation;
+                    *old_sprite = sprite;
+                    *visibility = Visibility::Visible;
+                }
+            } else if pool.spawned < MAX_PARTICLES {
+                pool.spawned += 1;
+                commands.spawn((
+                    SpriteBundle {
+                        sprite,
+                        transform: Transform::from_translation(translation),
+                        ..default()
+                    },
+                    particle,
+                    Name::new("Particle"),
+                ));
+            } else {
+                trace!("Particle pool exhausted, dropping the rest of {:?}", event.effect);
+                break;
+            }
+        }
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    effects: Res<ParticleEffects>,
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in particle_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        let emitter = effects.emitter(particle.effect);
+        let drag = (1.0 - emitter.drag * delta).max(0.0);
+        particle.velocity *= drag;
+        transform.translation += (particle.velocity * delta).extend(0.0);
+
+        let progress = particle.age / particle.lifetime;
+        sprite.color = emitter.color_at(progress);
+        sprite.custom_size = Some(Vec2::splat(emitter.size * emitter.scale_at(progress)));
+    }
+}
+
+fn clear_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(Entity, &mut Visibility), With<Particle>>,
+) {
+    for (entity, mut visibility) in particle_query.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+}
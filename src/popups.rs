@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::{FontResource, GameState};
+
+// further popups are dropped until one has faded
+pub const MAX_POPUPS: usize = 64;
+
+const POPUP_LIFETIME: f32 = 0.8;
+
+const POPUP_RISE: f32 = 45.0;
+
+// above the particles
+const POPUP_Z: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+    Damage,
+    PlayerDamage,
+    Mana,
+}
+
+impl PopupKind {
+    pub fn text(self, amount: i32) -> String {
+        match self {
+            PopupKind::Damage | PopupKind::PlayerDamage => format!("-{}", amount),
+            PopupKind::Mana => format!("+{} MP", amount),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PopupKind::Damage => Color::rgb(1.0, 0.95, 0.7),
+            PopupKind::PlayerDamage => Color::rgb(1.0, 0.3, 0.3),
+            PopupKind::Mana => Color::rgb(0.5, 0.75, 1.0),
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct SpawnPopup {
+    pub kind: PopupKind,
+    pub amount: i32,
+    pub position: Vec2,
+}
+
+// hidden popups wait in the PopupPool
+#[derive(Component, Debug)]
+pub struct Popup {
+    pub kind: PopupKind,
+    pub age: f32,
+}
+
+// so a burst of hits does not spawn a burst of entities
+#[derive(Resource, Debug, Default)]
+pub struct PopupPool {
+    pub free: Vec<Entity>,
+    pub spawned: usize,
+}
+
+pub struct PopupsPlugin;
+
+impl Plugin for PopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // events
+            .add_event::<SpawnPopup>()
+
+            // resources
+            .init_resource::<PopupPool>()
+
+            // on-exit: in game
+            .add_systems(OnExit(GameState::InGame), clear_popups)
+
+            // update systems
+            .add_systems(Update, (
+                update_popups,
+                show_popups,
+            ).chain());
+    }
+}
+
+fn show_popups(
+    mut commands: Commands,
+    mut er_spawn_popup: EventReader<SpawnPopup>,
+    font_res: Res<FontResource>,
+    mut pool: ResMut<PopupPool>,
+    mut popup_query: Query<(&mut Popup, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    // the jitter is cosmetic, so it must not draw from the gameplay rng
+    let mut rng = rand::thread_rng();
+
+    for event in er_spawn_popup.read() {
+        // spread popups of the same spot a little so they do not cover each other
+        let jitter = Vec2::new(rng.gen_range(-10.0..=10.0), rng.gen_range(0.0..=8.0));
+        let translation = (event.position + jitter).extend(POPUP_Z);
+        let popup = Popup {
+            kind: event.kind,
+            age: 0.0,
+        };
+        let value = event.kind.text(event.amount);
+        let color = event.kind.color();
+
+        if let Some(entity) = pool.free.pop() {
+            if let Ok((mut old, mut text, mut transform, mut visibility)) = popup_query.get_mut(entity) {
+                *old = popup;
+                text.sections[0].value = value;
+                text.sections[0].style.color = color;
+                transform.translation = translation;
+                *visibility = Visibility::Visible;
+            }
+        } else if pool.spawned < MAX_POPUPS {
+            pool.spawned += 1;
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        value,
+                        TextStyle {
+                            font: font_res.font.clone(),
+                            font_size: 18.0,
+                            color,
+                        },
+                    ),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                popup,
+                Name::new("Popup"),
+            ));
+        } else {
+            trace!("Too many popups, dropping {:?}", event);
+        }
+    }
+}
+
+fn update_popups(
+    time: Res<Time>,
+    mut pool: ResMut<PopupPool>,
+    mut popup_query: Query<(Entity, &mut Popup, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut popup, mut text, mut transform, mut visibility) in popup_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        popup.age += delta;
+        if popup.age >= POPUP_LIFETIME {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE * delta;
+        // fully opaque for the first half, then fade out
+        let progress = popup.age / POPUP_LIFETIME;
+        let alpha = (2.0 - 2.0 * progress).min(1.0);
+        text.sections[0].style.color = popup.kind.color().with_a(alpha);
+    }
+}
+
+fn clear_popups(
+    mut pool: ResMut<PopupPool>,
+    mut popup_query: Query<(Entity, &mut Visibility), With<Popup>>,
+) {
+    for (entity, mut visibility) in popup_query.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::balance::Balance;
+use crate::gameplay::*;
+use crate::rng::{setup_game_rng, GameRng, SeedConfig};
+use crate::GameState;
+
+// bump whenever the replay format or the simulation changes in a way that breaks old replays
+pub const REPLAY_VERSION: u32 = 8;
+
+// the input is stored as runs of identical ticks to keep the file small
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub tick_rate: f64,
+    pub mode: GameMode,
+    // Balance::fingerprint of the balance in use
+    pub balance: u64,
+    pub result: GameResult,
+    pub inputs: Vec<InputRun>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct InputRun {
+    pub ticks: u32,
+    pub movement: Option<(f32, f32)>,
+    pub spawn_minions: bool,
+}
+
+impl InputRun {
+    fn from_player_input(input: &PlayerInput) -> Self {
+        Self {
+            ticks: 1,
+            movement: input.movement.map(|movement| (movement.x, movement.y)),
+            spawn_minions: input.spawn_minions,
+        }
+    }
+
+    fn same_input(&self, other: &Self) -> bool {
+        self.movement == other.movement && self.spawn_minions == other.spawn_minions
+    }
+
+    fn to_player_input(self) -> PlayerInput {
+        PlayerInput {
+            movement: self.movement.map(|(x, y)| Vec2::new(x, y)),
+            spawn_minions: self.spawn_minions,
+        }
+    }
+}
+
+impl Replay {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::to_string(self)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.inputs.iter().map(|run| run.ticks as usize).sum()
+    }
+
+    pub fn input_at(&self, tick: usize) -> Option<&InputRun> {
+        let mut start = 0;
+        for run in self.inputs.iter() {
+            start += run.ticks as usize;
+            if tick < start {
+                return Some(run);
+            }
+        }
+        None
+    }
+}
+
+// nothing is written when unset
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ReplaySettings {
+    pub save_path: Option<String>,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct LastReplay(pub Replay);
+
+#[derive(Resource, Debug)]
+pub struct ReplayRecorder {
+    seed: u64,
+    tick_rate: f64,
+    mode: GameMode,
+    balance: u64,
+    inputs: Vec<InputRun>,
+}
+
+// insert it before the main menu is reached and the match starts on its own
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    tick: usize,
+    started: bool,
+    // what to return to once the playback is over
+    previous: Option<(TickRate, SeedConfig, GameMode)>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            started: false,
+            previous: None,
+        }
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let path = crate::args::launch_arg("replay")?;
+        match load_replay(&path) {
+            Ok(replay) if replay.version == REPLAY_VERSION => {
+                info!("Playing back replay {} (seed {})", path, replay.seed);
+                Some(Self::new(replay))
+            }
+            Ok(replay) => {
+                warn!(
+                    "Replay {} has version {}, expected {}; ignoring it",
+                    path, replay.version, REPLAY_VERSION
+                );
+                None
+            }
+            Err(error) => {
+                warn!("Unable to load replay {}: {}", path, error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn load_replay(path: &str) -> Result<Replay, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Replay::from_ron(&text).map_err(|e| e.to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn load_replay(_path: &str) -> Result<Replay, String> {
+    Err("replays are not supported on the web".to_string())
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_replay(path: &str, replay: &Replay) -> Result<(), String> {
+    let text = replay.to_ron().map_err(|e| e.to_string())?;
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn save_replay(_path: &str, _replay: &Replay) -> Result<(), String> {
+    Err("replays are not supported on the web".to_string())
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // resources
+            .init_resource::<ReplaySettings>()
+
+            // on-enter
+            .add_systems(OnEnter(GameState::MainMenu), start_playback)
+            .add_systems(OnEnter(GameState::InGame), (
+                start_recording.after(setup_game_rng),
+                detach_live_input.after(spawn_player),
+            ))
+            .add_systems(OnEnter(GameState::GameOver), (
+                finish_recording,
+                finish_playback,
+            ))
+
+            // on-exit: in game
+            .add_systems(OnExit(GameState::InGame), abandon_playback)
+
+            // fixed update systems
+            .add_systems(FixedUpdate, (
+                record_tick.before(handle_actions),
+                play_back_tick.before(handle_actions),
+            ).run_if(in_state(GameState::InGame).and_then(match_in_progress)));
+    }
+}
+
+fn start_recording(
+    mut commands: Commands,
+    game_rng: Res<GameRng>,
+    tick_rate: Res<TickRate>,
+    game_mode: Res<GameMode>,
+    balance: Res<Balance>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    commands.remove_resource::<ReplayRecorder>();
+    if playback.is_none() {
+        commands.insert_resource(ReplayRecorder {
+            seed: game_rng.seed(),
+            tick_rate: tick_rate.0,
+            mode: *game_mode,
+            balance: balance.fingerprint(),
+            inputs: Vec::new(),
+        });
+    }
+}
+
+fn record_tick(
+    recorder: Option<ResMut<ReplayRecorder>>,
+    input_query: Query<&PlayerInput, With<Player>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+
+    let run = match input_query.get_single() {
+        Ok(input) => InputRun::from_player_input(input),
+        Err(_) => InputRun::from_player_input(&PlayerInput::default()),
+    };
+
+    match recorder.inputs.last_mut() {
+        Some(last) if last.same_input(&run) => last.ticks += 1,
+        _ => recorder.inputs.push(run),
+    }
+}
+
+fn finish_recording(
+    mut commands: Commands,
+    recorder: Option<Res<ReplayRecorder>>,
+    game_status: Res<GameStatus>,
+    settings: Res<ReplaySettings>,
+) {
+    let Some(recorder) = recorder else {
+        return;
+    };
+
+    let replay = Replay {
+        version: REPLAY_VERSION,
+        seed: recorder.seed,
+        tick_rate: recorder.tick_rate,
+        mode: recorder.mode,
+        balance: recorder.balance,
+        result: game_status.result,
+        inputs: recorder.inputs.clone(),
+    };
+
+    if let Some(path) = &settings.save_path {
+        match save_replay(path, &replay) {
+            Ok(()) => info!("Saved replay of {} ticks to {}", replay.ticks(), path),
+            Err(error) => warn!("Unable to save replay to {}: {}", path, error),
+        }
+    }
+
+    commands.remove_resource::<ReplayRecorder>();
+    commands.insert_resource(LastReplay(replay));
+}
+
+fn start_playback(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    tick_rate: Res<TickRate>,
+    seed_config: Res<SeedConfig>,
+    game_mode: Res<GameMode>,
+    balance: Res<Balance>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(mut playback) = playback {
+        // the same inputs would play out differently under other balance values
+        if playback.replay.balance != balance.fingerprint() {
+            warn!("Replay was recorded with a different balance file; ignoring it");
+            commands.remove_resource::<ReplayPlayback>();
+            return;
+        }
+
+        if !playback.started {
+            playback.started = true;
+            playback.previous = Some((*tick_rate, *seed_config, *game_mode));
+            commands.insert_resource(TickRate(playback.replay.tick_rate));
+            commands.insert_resource(SeedConfig(Some(playback.replay.seed)));
+            commands.insert_resource(playback.replay.mode);
+            next_state.set(GameState::InGame);
+        }
+    }
+}
+
+fn detach_live_input(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    if let Some(mut playback) = playback {
+        // the recording always starts at the first tick of a match
+        playback.tick = 0;
+
+        for player in player_query.iter() {
+            commands.entity(player).remove::<InputMap<PlayerAction>>();
+        }
+    }
+}
+
+fn play_back_tick(
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut input_query: Query<&mut PlayerInput, With<Player>>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+
+    let tick = playback.tick;
+    playback.tick += 1;
+
+    if let Ok(mut input) = input_query.get_single_mut() {
+        *input = match playback.replay.input_at(tick) {
+            Some(run) => run.to_player_input(),
+            None => PlayerInput::default(),
+        };
+    }
+}
+
+fn finish_playback(
+    mut commands: Commands,
+    playback: Option<Res<ReplayPlayback>>,
+    game_status: Res<GameStatus>,
+) {
+    let Some(playback) = playback else {
+        return;
+    };
+
+    if game_status.result == playback.replay.result {
+        info!(
+            "Replay finished after {} of {} ticks with the recorded result ({:?})",
+            playback.tick,
+            playback.replay.ticks(),
+            game_status.result
+        );
+    } else {
+        warn!(
+            "Replay diverged: finished after {} of {} ticks with {:?}, recorded {:?}",
+            playback.tick,
+            playback.replay.ticks(),
+            game_status.result,
+            playback.replay.result
+        );
+    }
+
+    end_playback(&mut commands, &playback);
+}
+
+// quitting to the main menu in the middle of a replay
+fn abandon_playback(
+    mut commands: Commands,
+    playback: Option<Res<ReplayPlayback>>,
+    state: Res<State<GameState>>,
+) {
+    if let Some(playback) = playback {
+        if *state.get() != GameState::GameOver {
+            info!("Replay stopped after {} of {} ticks", playback.tick, playback.replay.ticks());
+            end_playback(&mut commands, &playback);
+        }
+    }
+}
+
+// hands control back to the player for the next match
+fn end_playback(commands: &mut Commands, playback: &ReplayPlayback) {
+    if let Some((tick_rate, seed_config, game_mode)) = playback.previous {
+        commands.insert_resource(tick_rate);
+        commands.insert_resource(seed_config);
+        commands.insert_resource(game_mode);
+    }
+    commands.remove_resource::<ReplayPlayback>();
+}
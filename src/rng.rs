@@ -0,0 +1,69 @@
+use bevy::log::info;
+use bevy::prelude::{Commands, Res, Resource};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use crate::args::launch_arg;
+
+// a fresh seed is generated for every match when unset
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct SeedConfig(pub Option<u64>);
+
+impl SeedConfig {
+    pub fn from_env() -> Self {
+        Self(launch_arg("seed").as_deref().and_then(parse_seed))
+    }
+}
+
+fn parse_seed(value: &str) -> Option<u64> {
+    match value.trim().parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            bevy::log::warn!("Ignoring invalid seed {:?}", value);
+            None
+        }
+    }
+}
+
+// every random decision must go through this resource so a match can be reproduced from its seed
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+pub fn setup_game_rng(mut commands: Commands, seed_config: Res<SeedConfig>) {
+    let seed = seed_config.0.unwrap_or_else(|| rand::thread_rng().gen());
+    info!("Starting match with seed {}", seed);
+    commands.insert_resource(GameRng::new(seed));
+}
@@ -0,0 +1,133 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadState};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+
+// checks the values of a file once it has parsed, reporting all of the invalid ones at once
+pub trait Validate {
+    fn validate(&self) -> Result<(), RonAssetError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(Vec<String>),
+}
+
+impl RonAssetError {
+    pub fn check(problems: Vec<String>) -> Result<(), Self> {
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Self::Invalid(problems))
+        }
+    }
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "unable to read the file: {}", error),
+            Self::Parse(error) => write!(f, "unable to parse the file: {}", error),
+            Self::Invalid(problems) => write!(f, "invalid values: {}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl From<std::io::Error> for RonAssetError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+pub fn parse_ron<T: DeserializeOwned + Validate>(text: &str) -> Result<T, RonAssetError> {
+    let value: T = ron::from_str(text)?;
+    value.validate()?;
+    Ok(value)
+}
+
+// loads files with the given extensions; a file that fails validation is reported by the asset
+// server and never replaces the one in use
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Asset + DeserializeOwned + Validate> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<T, RonAssetError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            parse_ron(&String::from_utf8_lossy(&bytes))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+// for files that are also a resource, like the balance. They are loaded outside of the asset
+// collections, since a file that fails to load would keep the loading state from ever finishing;
+// the bundled copy stays in use until the file has loaded
+#[derive(Resource)]
+pub struct RonFile<T: Asset>(pub Handle<T>);
+
+pub fn load_ron_file<T: Asset>(path: &'static str) -> impl Fn(Commands, Res<AssetServer>) {
+    move |mut commands: Commands, asset_server: Res<AssetServer>| {
+        commands.insert_resource(RonFile(asset_server.load::<T>(path)));
+    }
+}
+
+pub fn warn_if_not_loaded<T: Asset>(file: Res<RonFile<T>>, asset_server: Res<AssetServer>) {
+    if asset_server.load_state(&file.0) == LoadState::Failed {
+        warn!("No {} file could be loaded, using the bundled one", T::short_type_path());
+    }
+}
+
+pub fn apply_loaded<T: Asset + Resource + Clone>(
+    mut commands: Commands,
+    mut er_asset: EventReader<AssetEvent<T>>,
+    assets: Res<Assets<T>>,
+) {
+    for event in er_asset.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if let Some(loaded) = assets.get(*id) {
+                info!("{} file loaded, applying its values", T::short_type_path());
+                commands.insert_resource(loaded.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::bindings::fill_missing_bindings;
+use crate::gameplay::PlayerAction;
+use crate::storage::{load_ron, save_ron};
+use crate::SettingsState;
+
+// saved whenever the settings screen is closed
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    // 0 mutes
+    pub music_volume: f64,
+    // 0 mutes
+    pub effects_volume: f64,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    // 0 never shakes
+    pub screen_shake: f32,
+    pub bindings: InputMap<PlayerAction>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            fullscreen: false,
+            vsync: cfg!(target_family = "wasm"),
+            screen_shake: 1.0,
+            bindings: PlayerAction::default_input_map(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    // missing fields and actions keep their default value
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut settings: Self = ron::from_str(text)?;
+        fill_missing_bindings(&mut settings.bindings);
+        Ok(settings)
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SettingsStore {
+    pub path: Option<String>,
+}
+
+impl SettingsStore {
+    pub fn load(&self) -> Settings {
+        load_ron(self.path.as_deref(), "settings", Settings::from_ron)
+    }
+
+    pub fn save(&self, settings: &Settings) {
+        save_ron(self.path.as_deref(), "settings", || settings.to_ron())
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // resources
+            .init_resource::<SettingsStore>()
+            .init_resource::<Settings>()
+
+            // states
+            .init_state::<SettingsState>()
+
+            // startup systems
+            .add_systems(Startup, load_settings)
+
+            // on exit: settings
+            .add_systems(OnExit(SettingsState::Open), save_settings)
+            .add_systems(OnExit(SettingsState::Controls), save_settings)
+
+            // update systems
+            .add_systems(Update, apply_window_settings.run_if(resource_changed::<Settings>));
+    }
+}
+
+fn load_settings(mut commands: Commands, store: Res<SettingsStore>) {
+    commands.insert_resource(store.load());
+}
+
+fn save_settings(store: Res<SettingsStore>, settings: Res<Settings>) {
+    store.save(&settings);
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in window_query.iter_mut() {
+        window.mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        window.present_mode = if cfg!(target_family = "wasm") {
+            PresentMode::Fifo // required for wasm builds
+        } else if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::Immediate
+        };
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+// before the combo multiplier
+pub const KILL_SCORE: u32 = 50;
+
+pub const GEM_SCORE: u32 = 10;
+
+// seconds within which the next explosion has to hit to keep the combo going
+pub const COMBO_WINDOW: f32 = 1.5;
+
+const COMBO_STEP: f32 = 0.25;
+
+const MAX_COMBO_MULTIPLIER: f32 = 3.0;
+
+// damage dealt and kills score points, multiplied while explosions chain into a combo
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MatchStats {
+    pub score: u32,
+    pub time_survived: f32,
+    pub bombs_spawned: u32,
+    pub bombs_hit: u32,
+    pub gems_collected: u32,
+    // not counting mana over the maximum
+    pub mana_gained: u32,
+    // not counting overkill
+    pub damage_dealt: u32,
+    pub enemies_killed: u32,
+    pub player_hits: u32,
+    pub combo: u32,
+    pub best_combo: u32,
+    #[serde(skip)]
+    combo_timer: f32,
+}
+
+impl MatchStats {
+    pub fn combo_multiplier(&self) -> f32 {
+        let chained = self.combo.saturating_sub(1) as f32;
+        (1.0 + COMBO_STEP * chained).min(MAX_COMBO_MULTIPLIER)
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.bombs_spawned == 0 {
+            0.0
+        } else {
+            self.bombs_hit as f32 / self.bombs_spawned as f32
+        }
+    }
+
+    pub fn tick(&mut self, seconds: f32) {
+        self.time_survived += seconds;
+        if self.combo > 0 {
+            self.combo_timer -= seconds;
+            if self.combo_timer <= 0.0 {
+                self.combo = 0;
+            }
+        }
+    }
+
+    pub fn record_bomb_spawned(&mut self) {
+        self.bombs_spawned += 1;
+    }
+
+    pub fn record_bomb_hit(&mut self) {
+        self.bombs_hit += 1;
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+        self.combo_timer = COMBO_WINDOW;
+    }
+
+    pub fn record_damage(&mut self, amount: u32) {
+        self.damage_dealt += amount;
+        self.score += self.scaled(amount);
+    }
+
+    pub fn record_kill(&mut self) {
+        self.enemies_killed += 1;
+        self.score += self.scaled(KILL_SCORE);
+    }
+
+    pub fn record_gem(&mut self, mana_gained: u32) {
+        self.gems_collected += 1;
+        self.mana_gained += mana_gained;
+        self.score += GEM_SCORE;
+    }
+
+    pub fn record_player_hit(&mut self) {
+        self.player_hits += 1;
+    }
+
+    fn scaled(&self, points: u32) -> u32 {
+        (points as f32 * self.combo_multiplier()).round() as u32
+    }
+}
+
+pub fn format_duration(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,78 @@
+// plain files on desktop, localStorage entries keyed by path on the web
+
+use bevy::log::{info, warn};
+
+// what names the contents in the log; nothing is read or written without a path
+pub fn load_ron<T: Default>(
+    path: Option<&str>,
+    what: &str,
+    parse: impl FnOnce(&str) -> Result<T, ron::error::SpannedError>,
+) -> T {
+    let Some(path) = path else {
+        return T::default();
+    };
+
+    match load_text(path) {
+        Ok(Some(text)) => parse(&text).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {} in {}: {}", what, path, error);
+            T::default()
+        }),
+        Ok(None) => T::default(),
+        Err(error) => {
+            warn!("Unable to load {} from {}: {}", what, path, error);
+            T::default()
+        }
+    }
+}
+
+pub fn save_ron(path: Option<&str>, what: &str, to_ron: impl FnOnce() -> Result<String, ron::Error>) {
+    let Some(path) = path else {
+        return;
+    };
+
+    let result = to_ron()
+        .map_err(|e| e.to_string())
+        .and_then(|text| save_text(path, &text));
+    match result {
+        Ok(()) => info!("Saved {} to {}", what, path),
+        Err(error) => warn!("Unable to save {} to {}: {}", what, path, error),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn load_text(path: &str) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn save_text(path: &str, text: &str) -> Result<(), String> {
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| "localStorage is not available".to_string())
+}
+
+#[cfg(target_family = "wasm")]
+pub fn load_text(path: &str) -> Result<Option<String>, String> {
+    local_storage()?
+        .get_item(path)
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(target_family = "wasm")]
+pub fn save_text(path: &str, text: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(path, text)
+        .map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use ld55_summoning::achievements::*;
+use ld55_summoning::balance::Balance;
+use ld55_summoning::gameplay::*;
+use ld55_summoning::stats::MatchStats;
+use ld55_summoning::GameState;
+
+mod common;
+use common::{game_app, temp_path};
+
+fn achievements_app(path: &str) -> App {
+    // only the first wave, so clearing it wins the match
+    let mut balance = Balance::bundled();
+    balance.waves.list.truncate(1);
+
+    let mut app = game_app(AchievementsPlugin);
+    app.insert_resource(balance).insert_resource(AchievementStore {
+        path: Some(path.to_string()),
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn unlocked_ids(app: &App) -> Vec<String> {
+    let achievements = app.world.resource::<UnlockedAchievements>();
+    achievements.unlocked.iter().map(|unlocked| unlocked.id.clone()).collect()
+}
+
+#[test]
+fn whole_match_requirements_wait_for_the_result() {
+    let untouchable = Achievement::by_id("untouchable").unwrap();
+    let survivor = Achievement::by_id("survive_5_minutes").unwrap();
+    let stats = MatchStats::default();
+
+    assert!(!untouchable.is_met(&stats, GameResult::None));
+    assert!(untouchable.is_met(&stats, GameResult::Win));
+    assert!(!untouchable.is_met(&stats, GameResult::Lose));
+
+    let mut hit = MatchStats::default();
+    hit.record_player_hit();
+    assert!(!untouchable.is_met(&hit, GameResult::Win));
+
+    let mut alive = MatchStats::default();
+    alive.tick(299.0);
+    assert!(!survivor.is_met(&alive, GameResult::None));
+    alive.tick(1.0);
+    assert!(survivor.is_met(&alive, GameResult::None));
+}
+
+#[test]
+fn saved_achievements_drop_unknown_and_repeated_ids() {
+    let text = r#"(unlocked: [
+        (id: "first_win", date: "2024-04-14"),
+        (id: "retired", date: "2024-04-14"),
+        (id: "first_win", date: "2024-04-15"),
+    ])"#;
+    let achievements = UnlockedAchievements::from_ron(text).unwrap();
+    assert_eq!(achievements.unlocked.len(), 1);
+    assert_eq!(achievements.get("first_win").unwrap().date, "2024-04-14");
+
+    let text = achievements.to_ron().unwrap();
+    assert_eq!(UnlockedAchievements::from_ron(&text).unwrap(), achievements);
+
+    let path = temp_path("achievements-garbage");
+    std::fs::write(&path, "not achievements").unwrap();
+    let store = AchievementStore {
+        path: Some(path.clone()),
+    };
+    assert_eq!(store.load(), UnlockedAchievements::default());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn summoned_bombs_unlock_and_save_an_achievement() {
+    let path = temp_path("achievements-bombs");
+    let mut app = achievements_app(&path);
+
+    let mut er_unlocked = app.world.resource::<Events<AchievementUnlocked>>().get_reader();
+    for i in 0..100 {
+        app.world.send_event(SpawnMinionEvent(i as f32));
+    }
+    let mut toasts = 0;
+    for _ in 0..3 {
+        app.update();
+        toasts += er_unlocked.read(app.world.resource::<Events<AchievementUnlocked>>()).count();
+    }
+
+    assert_eq!(app.world.resource::<GameStatus>().stats.bombs_spawned, 100);
+    assert_eq!(unlocked_ids(&app), ["bombs_100"]);
+    assert_eq!(toasts, 1);
+
+    let saved = AchievementStore {
+        path: Some(path.clone()),
+    }
+    .load();
+    assert_eq!(saved, *app.world.resource::<UnlockedAchievements>());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn flawless_win_unlocks_the_win_achievements() {
+    let path = temp_path("achievements-win");
+    let mut app = achievements_app(&path);
+
+    let world = &mut app.world;
+    let enemies: Vec<Entity> = world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(world)
+        .collect();
+    for enemy in enemies.iter() {
+        world.send_event(DamageTakenEvent {
+            giver: *enemy,
+            receiver: *enemy,
+            amount: 1000,
+        });
+    }
+    for _ in 0..600 {
+        app.update();
+        if *app.world.resource::<State<GameState>>().get() == GameState::GameOver {
+            break;
+        }
+    }
+    assert_eq!(app.world.resource::<GameStatus>().result, GameResult::Win);
+
+    let stats = &app.world.resource::<GameStatus>().stats;
+    assert_eq!(stats.enemies_killed, enemies.len() as u32);
+    assert_eq!(stats.player_hits, 0);
+    assert_eq!(unlocked_ids(&app), ["first_win", "no_gems", "few_bombs", "untouchable"]);
+    std::fs::remove_file(&path).unwrap();
+}
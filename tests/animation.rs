@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+use ld55_summoning::animation::*;
+use ld55_summoning::gameplay::Health;
+
+fn tag(duration: f32, repeat: bool) -> AnimationTag {
+    AnimationTag {
+        duration,
+        repeat,
+        scale: vec![(1.0, 1.0), (2.0, 0.5)],
+        tint: Vec::new(),
+    }
+}
+
+fn animations() -> AnimationSet {
+    AnimationSet::from_ron(
+        "(size: (32.0, 32.0), tags: {
+            Idle: (duration: 1.0, repeat: true),
+            Move: (duration: 0.5, repeat: true, scale: [(1.0, 1.0), (1.5, 0.5)]),
+            Hurt: (duration: 0.2, repeat: false, tint: [(1.0, 0.0, 0.0, 1.0)]),
+            Death: (duration: 0.2, repeat: false),
+        })",
+    )
+    .unwrap()
+}
+
+fn animation_app() -> (App, Handle<AnimationSet>) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), SpriteAnimationPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(20)));
+    let animations = app.world.resource_mut::<Assets<AnimationSet>>().add(animations());
+    (app, animations)
+}
+
+#[test]
+fn bundled_animations_parse() {
+    for name in ["Sprite-Player", "Sprite-Enemy", "Sprite-Bomb", "Sprite-ManaGem"] {
+        let path = format!("assets/images/{}.anim.ron", name);
+        let text = std::fs::read_to_string(&path).unwrap();
+        let animations = AnimationSet::from_ron(&text).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        assert!(animations.tag(AnimationKind::Idle).is_some(), "{} has no idle tag", path);
+    }
+}
+
+#[test]
+fn tags_step_through_their_keyframes() {
+    let looping = tag(1.0, true);
+    assert_eq!(looping.scale(1.5), looping.scale(0.5));
+    assert_eq!(looping.scale(0.5), Vec2::new(1.5, 0.75));
+    assert!(!looping.finished(10.0));
+
+    let once = tag(1.0, false);
+    assert_eq!(once.scale(3.0), Vec2::new(2.0, 0.5));
+    assert!(once.finished(1.0));
+    assert_eq!(once.tint(0.5), None);
+}
+
+#[test]
+fn animation_follows_movement_and_damage() {
+    let (mut app, animations) = animation_app();
+    let slime = app
+        .world
+        .spawn((
+            SpriteBundle::default(),
+            SpriteAnimation::new(animations).with_base_size(Vec2::splat(40.0)),
+            Health { current: 10, max: 10 },
+        ))
+        .id();
+    app.update();
+    app.update();
+    assert_eq!(app.world.get::<SpriteAnimation>(slime).unwrap().kind, AnimationKind::Idle);
+
+    for _ in 0..5 {
+        app.world.get_mut::<Transform>(slime).unwrap().translation.x += 5.0;
+        app.update();
+    }
+    assert_eq!(app.world.get::<SpriteAnimation>(slime).unwrap().kind, AnimationKind::Move);
+    let size = app.world.get::<Sprite>(slime).unwrap().custom_size.unwrap();
+    assert!(size.x > 40.0 && size.y < 40.0, "slime is not squashed: {}", size);
+
+    app.world.get_mut::<Health>(slime).unwrap().current = 5;
+    app.update();
+    assert_eq!(app.world.get::<SpriteAnimation>(slime).unwrap().kind, AnimationKind::Hurt);
+    assert_eq!(app.world.get::<Sprite>(slime).unwrap().color, Color::rgba(1.0, 0.0, 0.0, 1.0));
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(app.world.get::<SpriteAnimation>(slime).unwrap().kind, AnimationKind::Idle);
+    assert_eq!(app.world.get::<Sprite>(slime).unwrap().color, Color::WHITE);
+}
+
+#[test]
+fn corpses_go_away_after_their_death_animation() {
+    let (mut app, animations) = animation_app();
+    let mut commands_queue = bevy::ecs::system::CommandQueue::default();
+    let mut commands = Commands::new(&mut commands_queue, &app.world);
+    spawn_corpse(
+        &mut commands,
+        &SpriteAnimation::new(animations),
+        Handle::default(),
+        &Sprite::default(),
+        Transform::default(),
+    );
+    commands_queue.apply(&mut app.world);
+
+    let mut corpses = app.world.query_filtered::<&SpriteAnimation, With<Corpse>>();
+    assert_eq!(corpses.single(&app.world).kind, AnimationKind::Death);
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(corpses.iter(&app.world).count(), 0);
+}
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_kira_audio::AudioChannel;
+use std::time::Duration;
+use ld55_summoning::audio::*;
+use ld55_summoning::gameplay::{Health, Player};
+use ld55_summoning::loading::AudioAssets;
+use ld55_summoning::music::*;
+use ld55_summoning::settings::Settings;
+use ld55_summoning::{GameState, PauseState};
+
+mod common;
+use common::game_app;
+
+fn audio_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, GameAudioPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+        .init_resource::<Settings>()
+        .init_resource::<AudioAssets>()
+        .init_resource::<AudioChannel<MusicChannel>>()
+        .init_resource::<AudioChannel<EffectsChannel>>();
+    app.update();
+    app
+}
+
+#[test]
+fn music_is_ducked_for_a_while_after_a_big_effect() {
+    let mut app = audio_app();
+    assert!(app.world.resource::<MusicDucking>().0.is_none());
+
+    app.world.send_event(PlaySfx::new(SfxKind::EnemyDie));
+    app.update();
+    assert!(app.world.resource::<MusicDucking>().0.is_some());
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world.resource::<MusicDucking>().0.is_none());
+}
+
+#[test]
+fn simultaneous_sounds_of_a_kind_are_limited() {
+    let mut app = audio_app();
+    for _ in 0..6 {
+        app.world.send_event(PlaySfx::new(SfxKind::ManaGem));
+    }
+    app.world.send_event(PlaySfx::new(SfxKind::Explosion));
+    app.update();
+
+    let instances = app.world.resource::<SfxInstances>();
+    assert_eq!(instances.count(SfxKind::ManaGem), SfxKind::ManaGem.spec().max_instances);
+    assert_eq!(instances.count(SfxKind::Explosion), 1);
+}
+
+#[test]
+fn gameplay_asks_for_sounds_through_events() {
+    let mut app = game_app(GameAudioPlugin);
+    app.update();
+
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(app.world.resource::<SfxInstances>().count(SfxKind::SpawnMinion), 1);
+}
+
+#[test]
+fn sounds_are_placed_relative_to_the_camera() {
+    let (volume, panning) = spatial_mix(Vec2::ZERO);
+    assert_eq!((volume, panning), (1.0, 0.5));
+
+    let (_, left) = spatial_mix(Vec2::new(-300.0, 0.0));
+    let (_, right) = spatial_mix(Vec2::new(300.0, 0.0));
+    assert!(left < 0.5 && right > 0.5);
+    assert_eq!(left, 1.0 - right);
+
+    let (near, _) = spatial_mix(Vec2::new(50.0, 50.0));
+    let (far, _) = spatial_mix(Vec2::new(380.0, 250.0));
+    assert!(far < near && far > 0.0);
+}
+
+fn music_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, MusicDirectorPlugin))
+        .init_state::<GameState>()
+        .init_state::<PauseState>()
+        .init_resource::<Settings>()
+        .init_resource::<MusicDucking>()
+        .init_resource::<AudioAssets>()
+        .init_resource::<AudioChannel<MusicChannel>>();
+    app.update();
+    app
+}
+
+#[test]
+fn music_crossfades_between_screens() {
+    let mut app = music_app();
+    assert_eq!(app.world.resource::<MusicDirector>().track, None);
+
+    let mut instances = Vec::new();
+    for (state, track) in [
+        (GameState::MainMenu, MusicTrack::Menu),
+        (GameState::InGame, MusicTrack::Combat),
+        (GameState::GameOver, MusicTrack::GameOver),
+        (GameState::InGame, MusicTrack::Combat),
+    ] {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+        let director = app.world.resource::<MusicDirector>();
+        assert_eq!(director.track, Some(track));
+        instances.push(director.instance.clone().unwrap());
+    }
+    instances.dedup();
+    assert_eq!(instances.len(), 4);
+
+    // restarting a match keeps the combat track playing
+    let playing = app.world.resource::<MusicDirector>().instance.clone();
+    app.world.resource_mut::<NextState<GameState>>().set(GameState::Restarting);
+    app.update();
+    assert_eq!(app.world.resource::<MusicDirector>().instance, playing);
+}
+
+#[test]
+fn combat_music_intensifies_with_danger() {
+    assert_eq!(combat_intensity(None, 1.0), 0.0);
+    assert_eq!(combat_intensity(Some(500.0), 1.0), 0.0);
+    assert_eq!(combat_intensity(Some(50.0), 1.0), 1.0);
+    assert_eq!(combat_intensity(None, 0.0), 1.0);
+    let near = combat_intensity(Some(150.0), 1.0);
+    assert!(near > 0.0 && near < 1.0);
+
+    let mut app = game_app(MusicDirectorPlugin);
+    app.init_resource::<MusicDucking>();
+    app.update();
+    app.update();
+    let calm = app.world.resource::<MusicDirector>().level();
+
+    let world = &mut app.world;
+    let mut player_health = world.query_filtered::<&mut Health, With<Player>>();
+    let mut health = player_health.single_mut(world);
+    health.current = 1;
+    app.update();
+    let director = app.world.resource::<MusicDirector>();
+    assert!(director.intensity > 0.5, "{}", director.intensity);
+    assert!(director.level() > calm);
+}
@@ -0,0 +1,117 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use bevy_xpbd_2d::prelude::Mass;
+use ld55_summoning::balance::{Balance, BalancePlugin};
+use ld55_summoning::gameplay::*;
+use ld55_summoning::ron_asset::*;
+use ld55_summoning::GameState;
+
+mod common;
+use common::game_app;
+
+#[test]
+fn bundled_balance_is_valid() {
+    assert_eq!(Balance::bundled().validate().ok(), Some(()));
+}
+
+#[test]
+fn every_invalid_value_is_reported() {
+    let mut balance = Balance::bundled();
+    balance.player.speed = 0.0;
+    balance.enemy.health = -5;
+    balance.tank.mass = 0.0;
+    balance.mana_gem.mana = -1;
+
+    match balance.validate() {
+        Err(RonAssetError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 4, "{:?}", problems);
+            assert!(problems[0].starts_with("player.speed"));
+            assert!(problems[1].starts_with("enemy.health"));
+            assert!(problems[2].starts_with("tank.mass"));
+            assert!(problems[3].starts_with("mana_gem.mana"));
+        }
+        other => panic!("expected validation errors, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_file_is_rejected() {
+    assert!(matches!(
+        Balance::from_ron("(player: (speed: 1.0))"),
+        Err(RonAssetError::Parse(_))
+    ));
+}
+
+#[test]
+fn match_uses_the_balance_resource() {
+    let mut balance = Balance::bundled();
+    balance.enemy.health = 42;
+    balance.enemy.mass = 250.0;
+    balance.player.mana = 7;
+
+    let mut app = game_app(());
+    app.insert_resource(balance);
+    app.update();
+
+    let world = &mut app.world;
+    let (enemy_health, enemy_mass) = world.query_filtered::<(&Health, &Mass), With<Enemy>>().single(world);
+    assert_eq!(enemy_health.max, 42);
+    assert_eq!(enemy_mass.0, 250.0);
+    let player_mana = world.query_filtered::<&Mana, With<Player>>().single(world);
+    assert_eq!(player_mana.current, 7);
+}
+
+#[test]
+fn balance_file_loads_through_the_asset_server() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Balance>()
+        .register_asset_loader(RonAssetLoader::<Balance>::new(&["balance.ron"]));
+
+    let handle: Handle<Balance> = app.world.resource::<AssetServer>().load("game.balance.ron");
+    for _ in 0..1000 {
+        app.update();
+        if let Some(balance) = app.world.resource::<Assets<Balance>>().get(&handle) {
+            assert_eq!(*balance, Balance::bundled());
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("balance file was not loaded");
+}
+
+#[test]
+fn invalid_balance_file_keeps_the_bundled_one_and_still_reaches_the_main_menu() {
+    let dir = std::env::temp_dir().join(format!("ld55-invalid-balance-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("game.balance.ron"), "(player: (speed: 1.0))").unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin {
+            file_path: dir.to_string_lossy().into_owned(),
+            ..default()
+        })
+        .add_plugins(BalancePlugin)
+        .init_state::<GameState>()
+        .add_loading_state(
+            LoadingState::new(GameState::AssetLoading).continue_to_state(GameState::MainMenu),
+        );
+
+    let mut failed = false;
+    for _ in 0..1000 {
+        app.update();
+        let file = app.world.resource::<RonFile<Balance>>();
+        failed |= app.world.resource::<AssetServer>().load_state(&file.0) == LoadState::Failed;
+        if failed && *app.world.resource::<State<GameState>>().get() == GameState::MainMenu {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(failed, "balance file did not fail to load");
+    assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::MainMenu);
+    assert_eq!(*app.world.resource::<Balance>(), Balance::bundled());
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use ld55_summoning::bindings::*;
+use ld55_summoning::gameplay::*;
+use ld55_summoning::settings::Settings;
+
+mod common;
+use common::game_app;
+
+#[test]
+fn rebinding_replaces_the_binding_on_the_same_device_only() {
+    let mut input_map = PlayerAction::default_input_map();
+    rebind_button(&mut input_map, PlayerAction::SpawnMinions, CapturedInput::Key(KeyCode::KeyB)).unwrap();
+
+    assert_eq!(
+        describe_bindings(&input_map, PlayerAction::SpawnMinions),
+        "Pad South, B"
+    );
+}
+
+#[test]
+fn inputs_bound_to_another_action_are_rejected() {
+    let mut input_map = PlayerAction::default_input_map();
+
+    assert_eq!(
+        rebind_button(&mut input_map, PlayerAction::Pause, CapturedInput::Key(KeyCode::Space)),
+        Err(PlayerAction::SpawnMinions)
+    );
+    assert_eq!(
+        rebind_button(&mut input_map, PlayerAction::SpawnMinions, CapturedInput::Key(KeyCode::KeyW)),
+        Err(PlayerAction::Move)
+    );
+    assert_eq!(
+        rebind_movement(&mut input_map, [KeyCode::KeyI, KeyCode::KeyK, KeyCode::KeyJ, KeyCode::Escape]),
+        Err(PlayerAction::Pause)
+    );
+    assert_eq!(input_map, PlayerAction::default_input_map());
+}
+
+#[test]
+fn movement_is_rebound_to_four_keys() {
+    let mut input_map = PlayerAction::default_input_map();
+    rebind_movement(&mut input_map, [KeyCode::KeyI, KeyCode::KeyK, KeyCode::KeyJ, KeyCode::KeyL]).unwrap();
+
+    assert_eq!(describe_bindings(&input_map, PlayerAction::Move), "Left Stick, I/J/K/L");
+
+    // the old keys are free again
+    assert_eq!(
+        conflicting_action(&input_map, PlayerAction::SpawnMinions, CapturedInput::Key(KeyCode::KeyW)),
+        None
+    );
+}
+
+#[test]
+fn rebound_controls_are_saved_with_the_settings() {
+    let mut settings = Settings::default();
+    rebind_button(&mut settings.bindings, PlayerAction::Pause, CapturedInput::Key(KeyCode::KeyP)).unwrap();
+
+    let loaded = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+    assert_eq!(loaded.bindings, settings.bindings);
+}
+
+#[test]
+fn actions_missing_from_saved_bindings_get_their_defaults() {
+    let mut settings = Settings::default();
+    settings.bindings.clear_action(&PlayerAction::Pause);
+
+    let loaded = Settings::from_ron(&settings.to_ron().unwrap()).unwrap();
+    assert_eq!(describe_bindings(&loaded.bindings, PlayerAction::Pause), "Escape, Pad Start");
+}
+
+#[test]
+fn player_uses_the_rebound_controls() {
+    let mut settings = Settings::default();
+    rebind_movement(&mut settings.bindings, [KeyCode::KeyI, KeyCode::KeyK, KeyCode::KeyJ, KeyCode::KeyL]).unwrap();
+
+    let mut app = game_app(());
+    app.insert_resource(settings);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    let start = world.query_filtered::<&Transform, With<Player>>().single(world).translation;
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyL);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    let end = world.query_filtered::<&Transform, With<Player>>().single(world).translation;
+    assert!(end.x > start.x);
+}
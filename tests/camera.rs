@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+use ld55_summoning::camera::*;
+use ld55_summoning::settings::Settings;
+use ld55_summoning::{CameraMarker, GameState, PauseState};
+
+fn camera_app(screen_shake: f32) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, CameraShakePlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(20)))
+        .insert_resource(Settings {
+            screen_shake,
+            ..Settings::default()
+        })
+        .insert_state(GameState::InGame)
+        .init_state::<PauseState>();
+    let camera = app.world.spawn((Transform::default(), CameraMarker)).id();
+    app.update();
+    (app, camera)
+}
+
+fn camera_offset(app: &App, camera: Entity) -> f32 {
+    app.world.get::<Transform>(camera).unwrap().translation.truncate().length()
+}
+
+#[test]
+fn explosions_shake_the_camera_until_the_trauma_wears_off() {
+    let (mut app, camera) = camera_app(1.0);
+    app.world.send_event(ShakeCamera(ShakeKind::Explosion));
+
+    let mut largest: f32 = 0.0;
+    for _ in 0..5 {
+        app.update();
+        largest = largest.max(camera_offset(&app, camera));
+    }
+    assert!(largest > 0.0);
+
+    for _ in 0..50 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<CameraTrauma>().trauma, 0.0);
+    assert_eq!(camera_offset(&app, camera), 0.0);
+}
+
+#[test]
+fn disabled_screen_shake_keeps_the_camera_still() {
+    let (mut app, camera) = camera_app(0.0);
+    app.world.send_event(ShakeCamera(ShakeKind::Explosion));
+    for _ in 0..5 {
+        app.update();
+        assert_eq!(camera_offset(&app, camera), 0.0);
+    }
+}
+
+#[test]
+fn heavy_hits_briefly_freeze_the_game() {
+    let (mut app, _) = camera_app(1.0);
+    app.world.send_event(ShakeCamera(ShakeKind::PlayerHit));
+    app.update();
+    assert_eq!(app.world.resource::<Time<Virtual>>().relative_speed(), 0.0);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(app.world.resource::<HitStop>().0.is_none());
+    assert_eq!(app.world.resource::<Time<Virtual>>().relative_speed(), 1.0);
+}
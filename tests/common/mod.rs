@@ -0,0 +1,27 @@
+// helpers shared by the integration tests; not every test uses all of them
+#![allow(dead_code)]
+
+use bevy::app::Plugins;
+use bevy::prelude::*;
+use ld55_summoning::gameplay::GameplayPlugin;
+use ld55_summoning::headless::HeadlessPlugin;
+use ld55_summoning::GameState;
+
+pub fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("ld55-{}-{}.ron", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+// a match without a window or assets, already in game, plus whatever plugins the test is about
+pub fn game_app<M>(plugins: impl Plugins<M>) -> App {
+    app_in_state(GameState::InGame, plugins)
+}
+
+pub fn app_in_state<M>(state: GameState, plugins: impl Plugins<M>) -> App {
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin, GameplayPlugin::default()))
+        .add_plugins(plugins)
+        .insert_state(state);
+    app
+}
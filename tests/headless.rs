@@ -0,0 +1,565 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+use ld55_summoning::balance::{Balance, EnemyKind, Wall, WaveGroup};
+use ld55_summoning::bars::{BarStat, StatBar};
+use ld55_summoning::gameplay::*;
+use bevy_xpbd_2d::prelude::{Collider, CollisionLayers, LinearVelocity, Position, RigidBody};
+use ld55_summoning::rng::{GameRng, SeedConfig};
+use ld55_summoning::{GameState, PauseState};
+
+mod common;
+use common::game_app;
+
+const MAX_FRAMES: usize = 60 * 60;
+
+fn headless_app() -> App {
+    game_app(())
+}
+
+fn run_until_game_over(app: &mut App) -> usize {
+    for frame in 0..MAX_FRAMES {
+        app.update();
+        if *app.world.resource::<State<GameState>>().get() == GameState::GameOver {
+            return frame;
+        }
+    }
+    panic!("match did not finish within {} frames", MAX_FRAMES);
+}
+
+#[test]
+fn arena_is_populated_on_enter() {
+    let mut app = headless_app();
+    app.update();
+
+    let world = &mut app.world;
+    assert_eq!(world.query::<&Player>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Enemy>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Minion>().iter(world).count(), 0);
+}
+
+#[test]
+fn moving_player_is_caught_by_the_enemy() {
+    let mut app = headless_app();
+
+    // walk along the bottom wall towards the enemy's corner
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyD);
+    run_until_game_over(&mut app);
+
+    assert_eq!(app.world.resource::<GameStatus>().result, GameResult::Lose);
+
+    let world = &mut app.world;
+    assert_eq!(world.query::<&Player>().iter(world).count(), 0);
+}
+
+#[test]
+fn mana_gems_spawn_over_time() {
+    let mut app = headless_app();
+    for _ in 0..(60 * 3) {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    assert!(world.query::<&ManaGem>().iter(world).count() > 0);
+}
+
+fn press_escape(app: &mut App) {
+    let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+    keys.release(KeyCode::Escape);
+    app.update();
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Escape);
+    for _ in 0..2 {
+        app.update();
+    }
+}
+
+#[test]
+fn escape_pauses_and_resumes_the_match() {
+    let mut app = headless_app();
+    for _ in 0..3 {
+        app.update();
+    }
+
+    press_escape(&mut app);
+    assert_eq!(*app.world.resource::<State<PauseState>>().get(), PauseState::Paused);
+
+    // nothing moves while paused
+    let paused_at = enemy_position(&mut app);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(enemy_position(&mut app), paused_at);
+
+    press_escape(&mut app);
+    assert_eq!(*app.world.resource::<State<PauseState>>().get(), PauseState::Running);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_ne!(enemy_position(&mut app), paused_at);
+}
+
+fn gem_positions(app: &mut App) -> Vec<Vec2> {
+    let world = &mut app.world;
+    let mut positions: Vec<Vec2> = world
+        .query_filtered::<&Position, With<ManaGem>>()
+        .iter(world)
+        .map(|position| position.0)
+        .collect();
+    positions.sort_by(|a, b| a.x.total_cmp(&b.x));
+    positions
+}
+
+#[test]
+fn same_seed_spawns_the_same_gems() {
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let mut app = headless_app();
+        app.insert_resource(SeedConfig(Some(1234)));
+        for _ in 0..(60 * 3) {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<GameRng>().seed(), 1234);
+        runs.push(gem_positions(&mut app));
+    }
+
+    assert!(!runs[0].is_empty());
+    assert_eq!(runs[0], runs[1]);
+}
+
+fn enemy_position(app: &mut App) -> Vec2 {
+    let world = &mut app.world;
+    world
+        .query_filtered::<&Position, With<Enemy>>()
+        .single(world)
+        .0
+}
+
+fn enemy_positions_over_one_second(frame_time: Duration) -> (Vec2, Vec2) {
+    let mut app = headless_app();
+    app.insert_resource(SeedConfig(Some(1234)))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+    app.update();
+
+    let start = enemy_position(&mut app);
+    while app.world.resource::<Time<Fixed>>().elapsed() < Duration::from_secs(1) {
+        app.update();
+    }
+    (start, enemy_position(&mut app))
+}
+
+#[test]
+fn simulation_is_independent_of_frame_rate() {
+    let at_60_fps = enemy_positions_over_one_second(Duration::from_secs(1) / 60);
+    let at_500_fps = enemy_positions_over_one_second(Duration::from_secs(1) / 500);
+
+    assert_ne!(at_60_fps.0, at_60_fps.1);
+    assert_eq!(at_60_fps, at_500_fps);
+}
+
+#[test]
+fn thrown_bombs_explode_once_their_fuse_runs_out() {
+    let mut app = headless_app();
+    app.update();
+
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    app.update();
+    app.world.resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::Space);
+    app.update();
+
+    let world = &mut app.world;
+    assert_eq!(world.query::<&Minion>().iter(world).count(), 2);
+
+    let fuse = Balance::bundled().minion.fuse;
+    for _ in 0..((fuse * 60.0) as usize + 10) {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    assert_eq!(world.query::<&Minion>().iter(world).count(), 0);
+}
+
+#[test]
+fn bomb_blast_hits_the_player_but_not_the_distant_enemy() {
+    let mut app = headless_app();
+    app.update();
+
+    let world = &mut app.world;
+    let player_position = world
+        .query_filtered::<&Position, With<Player>>()
+        .single(world)
+        .0;
+    world.spawn((
+        Minion,
+        Name::new("Minion"),
+        Position(player_position),
+        Fuse {
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        },
+    ));
+
+    // stop as soon as the match is decided, before the arena is cleaned up
+    for _ in 0..60 {
+        app.update();
+        if app.world.resource::<GameStatus>().result != GameResult::None {
+            break;
+        }
+    }
+
+    assert_eq!(app.world.resource::<GameStatus>().result, GameResult::Lose);
+
+    let world = &mut app.world;
+    let enemy_health = world.query_filtered::<&Health, With<Enemy>>().single(world);
+    assert_eq!(enemy_health.current, enemy_health.max);
+}
+
+fn hurt_player(app: &mut App, giver: Entity, amount: i32) {
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+    app.world.send_event(DamageTakenEvent {
+        giver,
+        receiver: player,
+        amount,
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn player_health(app: &mut App) -> i32 {
+    let world = &mut app.world;
+    world.query_filtered::<&Health, With<Player>>().single(world).current
+}
+
+#[test]
+fn hurt_player_is_briefly_invulnerable() {
+    let mut app = headless_app();
+    app.update();
+    let enemy = app
+        .world
+        .query_filtered::<Entity, With<Enemy>>()
+        .single(&app.world);
+    let max = player_health(&mut app);
+
+    hurt_player(&mut app, enemy, 3);
+    assert_eq!(player_health(&mut app), max - 3);
+
+    // a second hit right away is ignored
+    hurt_player(&mut app, enemy, 3);
+    assert_eq!(player_health(&mut app), max - 3);
+
+    // once the invulnerability wears off the player can be hurt again
+    let world = &mut app.world;
+    world.query_filtered::<&mut Invulnerable, With<Player>>().single_mut(world).timer =
+        Timer::from_seconds(0.0, TimerMode::Once);
+    for _ in 0..3 {
+        app.update();
+    }
+    let world = &mut app.world;
+    assert_eq!(world.query_filtered::<&Invulnerable, With<Player>>().iter(world).count(), 0);
+    assert_eq!(
+        *world.query_filtered::<&Visibility, With<Player>>().single(world),
+        Visibility::Inherited
+    );
+
+    hurt_player(&mut app, enemy, 3);
+    assert_eq!(player_health(&mut app), max - 6);
+}
+
+fn shoot_player(app: &mut App, damage: i32) {
+    let world = &mut app.world;
+    let position = world.query_filtered::<&Position, With<Player>>().single(world).0;
+    world.spawn((
+        Projectile { damage },
+        RigidBody::Kinematic,
+        Collider::circle(8.0),
+        CollisionLayers::new(GameLayer::Projectile, [GameLayer::Player]),
+        Position(position),
+    ));
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn second_projectile_hit_is_absorbed_by_the_invulnerability() {
+    // a lone tank far away, so nothing but the projectiles reaches the player
+    let mut balance = Balance::bundled();
+    balance.waves.list[0].groups = vec![WaveGroup {
+        kind: EnemyKind::Tank,
+        count: 1,
+        health_multiplier: 1.0,
+        walls: vec![Wall::Right],
+    }];
+    let damage = balance.ranged.projectile_damage;
+    let max = balance.player.health;
+    assert!(damage < max);
+
+    let mut app = headless_app();
+    app.insert_resource(balance);
+    app.update();
+
+    shoot_player(&mut app, damage);
+    assert_eq!(player_health(&mut app), max - damage);
+    let world = &mut app.world;
+    assert_eq!(world.query_filtered::<&Invulnerable, With<Player>>().iter(world).count(), 1);
+
+    shoot_player(&mut app, damage);
+    assert_eq!(player_health(&mut app), max - damage);
+    let world = &mut app.world;
+    assert_eq!(world.query::<&Projectile>().iter(world).count(), 0);
+}
+
+fn player_bar(app: &mut App, stat: BarStat) -> (f32, f32) {
+    let world = &mut app.world;
+    let player = world.query_filtered::<Entity, With<Player>>().single(world);
+    let bar = world
+        .query::<&StatBar>()
+        .iter(world)
+        .find(|bar| bar.target == player && bar.stat == stat)
+        .expect("the player has a bar for every stat");
+    (bar.fill, bar.trail)
+}
+
+#[test]
+fn player_bars_trail_behind_lost_health() {
+    let mut app = headless_app();
+    app.update();
+    assert_eq!(player_bar(&mut app, BarStat::Health), (1.0, 1.0));
+    assert_eq!(player_bar(&mut app, BarStat::Mana), (1.0, 1.0));
+
+    let enemy = app
+        .world
+        .query_filtered::<Entity, With<Enemy>>()
+        .single(&app.world);
+    let max = player_health(&mut app);
+    hurt_player(&mut app, enemy, max / 2);
+    assert_eq!(player_bar(&mut app, BarStat::Health), (0.5, 1.0));
+
+    // the lost segment catches up with the fill after a moment
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_eq!(player_bar(&mut app, BarStat::Health), (0.5, 0.5));
+}
+
+#[test]
+fn hurt_player_is_knocked_away_from_the_attacker() {
+    let mut app = headless_app();
+    app.update();
+
+    // put an attacker left of the player, who starts in the bottom left corner
+    let world = &mut app.world;
+    let player_position = world
+        .query_filtered::<&Position, With<Player>>()
+        .single(world)
+        .0;
+    let attacker = world
+        .spawn(Position(player_position - Vec2::new(50.0, 0.0)))
+        .id();
+
+    hurt_player(&mut app, attacker, 1);
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    let knocked_position = world
+        .query_filtered::<&Position, With<Player>>()
+        .single(world)
+        .0;
+    assert!(knocked_position.x > player_position.x + 40.0);
+}
+
+#[test]
+fn slime_splits_below_its_threshold() {
+    let mut app = headless_app();
+    app.update();
+
+    let world = &mut app.world;
+    let mut health = world.query_filtered::<&mut Health, With<Enemy>>().single_mut(world);
+    health.current = 100;
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let world = &mut app.world;
+    let children: Vec<(u32, i32)> = world
+        .query::<(&Slime, &Health)>()
+        .iter(world)
+        .map(|(slime, health)| (slime.generation, health.max))
+        .collect();
+    assert_eq!(children, vec![(1, 50), (1, 50)]);
+}
+
+fn kill_all_enemies(app: &mut App) {
+    let world = &mut app.world;
+    let enemies: Vec<Entity> = world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(world)
+        .collect();
+    for enemy in enemies {
+        world.send_event(DamageTakenEvent {
+            giver: enemy,
+            receiver: enemy,
+            amount: 1000,
+        });
+    }
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn enemy_count(app: &mut App) -> usize {
+    let world = &mut app.world;
+    world.query::<&Enemy>().iter(world).count()
+}
+
+fn single_wave_balance() -> Balance {
+    let mut balance = Balance::bundled();
+    balance.waves.list.truncate(1);
+    balance
+}
+
+#[test]
+fn clearing_a_wave_starts_the_next_one_after_an_intermission() {
+    let mut app = headless_app();
+    app.update();
+    kill_all_enemies(&mut app);
+
+    assert!(app.world.resource::<WaveManager>().intermission.is_some());
+    assert_eq!(enemy_count(&mut app), 0);
+
+    let balance = Balance::bundled();
+    for _ in 0..((balance.waves.intermission * 60.0) as usize + 10) {
+        app.update();
+    }
+
+    let waves = app.world.resource::<WaveManager>();
+    assert_eq!(waves.wave, 1);
+    assert!(waves.intermission.is_none());
+    let expected: u32 = balance.waves.groups(1).iter().map(|group| group.count).sum();
+    assert_eq!(enemy_count(&mut app), expected as usize);
+}
+
+#[test]
+fn clearing_the_last_wave_wins() {
+    let mut app = headless_app();
+    app.insert_resource(single_wave_balance());
+    app.update();
+    kill_all_enemies(&mut app);
+    run_until_game_over(&mut app);
+
+    assert_eq!(app.world.resource::<GameStatus>().result, GameResult::Win);
+}
+
+#[test]
+fn endless_mode_continues_past_the_last_wave() {
+    let mut app = headless_app();
+    app.insert_resource(single_wave_balance())
+        .insert_resource(GameMode::Endless);
+    app.update();
+    kill_all_enemies(&mut app);
+
+    assert_eq!(app.world.resource::<GameStatus>().result, GameResult::None);
+    assert!(app.world.resource::<WaveManager>().intermission.is_some());
+}
+
+// the player survives long enough to watch the enemy
+fn app_with_first_wave(kind: EnemyKind) -> App {
+    let mut balance = Balance::bundled();
+    balance.player.health = 1000;
+    balance.waves.list[0].groups = vec![WaveGroup {
+        kind,
+        count: 1,
+        health_multiplier: 1.0,
+        walls: vec![Wall::Right],
+    }];
+
+    let mut app = headless_app();
+    app.insert_resource(balance);
+
+    // let the physics set the enemy up
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+#[test]
+fn dasher_telegraphs_then_lunges() {
+    let mut app = app_with_first_wave(EnemyKind::Dasher);
+    let balance = Balance::bundled();
+
+    let mut phases = Vec::new();
+    let mut lunge_speed: f32 = 0.0;
+    let frames = (balance.dasher.cooldown + balance.dasher.telegraph + balance.dasher.lunge) * 60.0;
+    for _ in 0..(frames as usize + 10) {
+        app.update();
+
+        let world = &mut app.world;
+        let (dasher, velocity) = world.query::<(&Dasher, &LinearVelocity)>().single(world);
+        if phases.last() != Some(&dasher.phase) {
+            phases.push(dasher.phase);
+        }
+        if dasher.phase == DashPhase::Lunge {
+            lunge_speed = lunge_speed.max(velocity.length());
+        }
+    }
+
+    // damping takes a little off the top
+    assert!(lunge_speed > balance.dasher.lunge_speed * 0.9, "lunged at {}", lunge_speed);
+
+    assert_eq!(
+        phases,
+        vec![DashPhase::Approach, DashPhase::Telegraph, DashPhase::Lunge, DashPhase::Approach]
+    );
+}
+
+#[test]
+fn ranged_slime_shoots_the_player() {
+    let mut app = app_with_first_wave(EnemyKind::Ranged);
+
+    let mut fired = false;
+    for _ in 0..(60 * 10) {
+        app.update();
+
+        let world = &mut app.world;
+        fired |= world.query::<&Projectile>().iter(world).count() > 0;
+        let health = world.query_filtered::<&Health, With<Player>>().single(world);
+        if health.current < health.max {
+            break;
+        }
+    }
+
+    assert!(fired);
+    let world = &mut app.world;
+    let health = world.query_filtered::<&Health, With<Player>>().single(world);
+    assert_eq!(health.max - health.current, Balance::bundled().ranged.projectile_damage);
+}
+
+#[test]
+fn tank_takes_reduced_blast_damage() {
+    let mut app = app_with_first_wave(EnemyKind::Tank);
+
+    let world = &mut app.world;
+    let tank_position = world.query_filtered::<&Position, With<Tank>>().single(world).0;
+    world.spawn((
+        Minion,
+        Name::new("Minion"),
+        Position(tank_position),
+        Fuse {
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        },
+    ));
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let balance = Balance::bundled();
+    let expected = (balance.minion.damage as f32 * balance.tank.blast_damage_taken).round() as i32;
+    let world = &mut app.world;
+    let health = world.query_filtered::<&Health, With<Tank>>().single(world);
+    assert_eq!(health.max - health.current, expected);
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use ld55_summoning::achievements::AchievementsPlugin;
+use ld55_summoning::gameplay::*;
+use bevy::window::ReceivedCharacter;
+use ld55_summoning::highscores::*;
+use ld55_summoning::menus::{MenusPlugin, NameEntry};
+use ld55_summoning::GameState;
+
+mod common;
+use common::{game_app, temp_path};
+
+fn entry(name: &str, score: u32) -> HighScoreEntry {
+    HighScoreEntry {
+        name: name.to_string(),
+        score,
+        seed: 1234,
+        mode: GameMode::Standard,
+        date: "2024-04-14".to_string(),
+        time_survived: 90.0,
+    }
+}
+
+#[test]
+fn table_keeps_the_ten_best_scores_in_order() {
+    let mut high_scores = HighScores::default();
+    for score in 1..=12 {
+        high_scores.insert(entry("slime", score * 100));
+    }
+    assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.entries[0].score, 1200);
+    assert_eq!(high_scores.entries[9].score, 300);
+
+    assert!(!high_scores.qualifies(300));
+    assert_eq!(high_scores.insert(entry("late", 300)), None);
+    // ties go below the score that was there first
+    assert_eq!(high_scores.insert(entry("tie", 1100)), Some(2));
+    assert_eq!(high_scores.entries[1].name, "slime");
+    assert!(!HighScores::default().qualifies(0));
+}
+
+#[test]
+fn table_survives_a_round_trip() {
+    let mut high_scores = HighScores::default();
+    high_scores.insert(entry("first", 500));
+    high_scores.insert(entry("second", 200));
+
+    let text = high_scores.to_ron().unwrap();
+    assert_eq!(HighScores::from_ron(&text).unwrap(), high_scores);
+}
+
+#[test]
+fn damaged_table_keeps_its_readable_entries() {
+    let mut high_scores = HighScores::default();
+    high_scores.insert(entry("first", 500));
+    high_scores.insert(entry("second", 200));
+    high_scores.insert(entry("third", 100));
+
+    // cut off halfway through the second entry
+    let text = high_scores.to_ron().unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let damaged = format!("{}\n{}\n{}\n{}", lines[0], lines[1], &lines[2][..20], lines[3]);
+
+    let recovered = HighScores::from_ron(&damaged).unwrap();
+    let names: Vec<&str> = recovered.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["first", "third"]);
+
+    let path = temp_path("highscores-garbage");
+    std::fs::write(&path, "not a table").unwrap();
+    let store = HighScoreStore {
+        path: Some(path.clone()),
+    };
+    assert_eq!(store.load(), HighScores::default());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_713_052_800 + 86_399), "2024-04-14");
+}
+
+#[test]
+fn qualifying_match_is_saved_from_the_game_over_screen() {
+    let path = temp_path("highscores-game-over");
+    let mut app = game_app(HighScoresPlugin);
+    app.insert_resource(HighScoreStore {
+        path: Some(path.clone()),
+    })
+    .insert_resource(GameMode::Endless);
+    app.update();
+
+    app.world.resource_mut::<GameStatus>().stats.score = 750;
+    app.world.resource_mut::<NextState<GameState>>().set(GameState::GameOver);
+    app.update();
+    let pending = app.world.resource::<PendingHighScore>().0.clone();
+    assert_eq!(pending.score, 750);
+    assert_eq!(pending.mode, GameMode::Endless);
+
+    app.world.send_event(SaveHighScore {
+        name: "  Bomber\n".to_string(),
+    });
+    app.update();
+    assert!(app.world.get_resource::<PendingHighScore>().is_none());
+    assert_eq!(*app.world.resource::<LastHighScoreRank>(), LastHighScoreRank(0));
+
+    let saved = HighScoreStore {
+        path: Some(path.clone()),
+    }
+    .load();
+    assert_eq!(saved.entries.len(), 1);
+    assert_eq!(saved.entries[0].name, "Bomber");
+    assert_eq!(saved.entries[0].seed, pending.seed);
+    assert_eq!(saved, *app.world.resource::<HighScores>());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn game_over_screen_asks_for_the_name_of_a_qualifying_match() {
+    let mut app = game_app((HighScoresPlugin, AchievementsPlugin, MenusPlugin));
+    app.add_plugins(AssetPlugin::default())
+        .init_asset::<Font>()
+        .init_asset::<Image>()
+        .add_event::<ReceivedCharacter>();
+    app.update();
+
+    app.world.resource_mut::<GameStatus>().stats.score = 750;
+    app.world.resource_mut::<NextState<GameState>>().set(GameState::GameOver);
+    app.update();
+    assert!(app.world.contains_resource::<PendingHighScore>());
+    assert!(app.world.contains_resource::<NameEntry>());
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+use ld55_summoning::balance::Balance;
+use ld55_summoning::particles::*;
+use ld55_summoning::ron_asset::*;
+use ld55_summoning::GameState;
+
+mod common;
+use common::game_app;
+
+fn particles_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ParticlesPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(50)))
+        .init_state::<GameState>();
+    app.update();
+    app
+}
+
+fn live_particles(app: &mut App) -> usize {
+    app.world
+        .query::<(&Particle, &Visibility)>()
+        .iter(&app.world)
+        .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+        .count()
+}
+
+#[test]
+fn bundled_particles_are_valid() {
+    assert_eq!(ParticleEffects::bundled().validate().ok(), Some(()));
+}
+
+#[test]
+fn invalid_emitters_are_reported() {
+    let mut effects = ParticleEffects::bundled();
+    effects.explosion.lifetime = (1.0, 0.5);
+    effects.slime_death.size = 0.0;
+
+    match effects.validate() {
+        Err(RonAssetError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2, "{:?}", problems);
+            assert!(problems[0].starts_with("explosion.lifetime"));
+            assert!(problems[1].starts_with("slime_death"));
+            assert!(problems[1].ends_with("size must be positive"));
+        }
+        other => panic!("expected validation errors, got {:?}", other),
+    }
+}
+
+#[test]
+fn dead_particles_are_reused() {
+    let mut app = particles_app();
+    let count = ParticleEffects::bundled().explosion.count as usize;
+
+    app.world.send_event(SpawnParticles {
+        effect: ParticleEffect::Explosion,
+        position: Vec2::ZERO,
+    });
+    app.update();
+    assert_eq!(live_particles(&mut app), count);
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(live_particles(&mut app), 0);
+    assert_eq!(app.world.resource::<ParticlePool>().free.len(), count);
+
+    app.world.send_event(SpawnParticles {
+        effect: ParticleEffect::Explosion,
+        position: Vec2::ZERO,
+    });
+    app.update();
+    assert_eq!(live_particles(&mut app), count);
+    assert_eq!(app.world.resource::<ParticlePool>().spawned, count);
+}
+
+#[test]
+fn particle_count_is_capped() {
+    let mut app = particles_app();
+    for _ in 0..100 {
+        app.world.send_event(SpawnParticles {
+            effect: ParticleEffect::Explosion,
+            position: Vec2::ZERO,
+        });
+    }
+    app.update();
+    assert_eq!(live_particles(&mut app), MAX_PARTICLES);
+}
+
+#[test]
+fn exploding_bombs_emit_particles() {
+    let mut app = game_app((AssetPlugin::default(), ParticlesPlugin));
+    app.update();
+
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    app.update();
+    app.world.resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::Space);
+
+    let fuse = Balance::bundled().minion.fuse;
+    for _ in 0..((fuse * 60.0) as usize + 10) {
+        app.update();
+    }
+    assert!(live_particles(&mut app) > 0);
+}
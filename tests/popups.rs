@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use ld55_summoning::gameplay::*;
+use ld55_summoning::popups::*;
+
+mod common;
+use common::game_app;
+
+fn popup_app() -> App {
+    let mut app = game_app(PopupsPlugin);
+    app.update();
+    app
+}
+
+fn shown_popups(app: &mut App) -> Vec<(String, Color)> {
+    app.world
+        .query::<(&Popup, &Text, &Visibility)>()
+        .iter(&app.world)
+        .filter(|(_, _, visibility)| **visibility != Visibility::Hidden)
+        .map(|(_, text, _)| (text.sections[0].value.clone(), text.sections[0].style.color))
+        .collect()
+}
+
+#[test]
+fn popup_text_matches_its_kind() {
+    assert_eq!(PopupKind::Damage.text(20), "-20");
+    assert_eq!(PopupKind::Mana.text(10), "+10 MP");
+    assert_ne!(PopupKind::Damage.color(), PopupKind::PlayerDamage.color());
+}
+
+#[test]
+fn damage_shows_a_popup_that_fades_away() {
+    let mut app = popup_app();
+    let enemy = app.world.query_filtered::<Entity, With<Enemy>>().single(&app.world);
+    let player = app.world.query_filtered::<Entity, With<Player>>().single(&app.world);
+    app.world.send_event(DamageTakenEvent {
+        giver: player,
+        receiver: enemy,
+        amount: 3,
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+    let popups = shown_popups(&mut app);
+    assert_eq!(popups.len(), 1);
+    assert_eq!(popups[0].0, "-3");
+
+    for _ in 0..60 {
+        app.update();
+    }
+    assert!(shown_popups(&mut app).is_empty());
+    assert_eq!(app.world.resource::<PopupPool>().free.len(), 1);
+}
+
+#[test]
+fn popups_are_pooled() {
+    let mut app = popup_app();
+    for round in 0..2 {
+        for _ in 0..(MAX_POPUPS + 10) {
+            app.world.send_event(SpawnPopup {
+                kind: PopupKind::Damage,
+                amount: 1,
+                position: Vec2::ZERO,
+            });
+        }
+        app.update();
+        assert_eq!(shown_popups(&mut app).len(), MAX_POPUPS, "round {}", round);
+        for _ in 0..60 {
+            app.update();
+        }
+    }
+    assert_eq!(app.world.resource::<PopupPool>().spawned, MAX_POPUPS);
+}
+
+#[test]
+fn overkill_shows_only_the_health_that_was_left() {
+    let mut app = popup_app();
+    let world = &mut app.world;
+    let (enemy, health) = world
+        .query_filtered::<(Entity, &Health), With<Enemy>>()
+        .iter(world)
+        .map(|(enemy, health)| (enemy, health.current))
+        .next()
+        .unwrap();
+    world.send_event(DamageTakenEvent {
+        giver: enemy,
+        receiver: enemy,
+        amount: health + 1000,
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let popups = shown_popups(&mut app);
+    assert_eq!(popups.len(), 1);
+    assert_eq!(popups[0].0, format!("-{}", health));
+}
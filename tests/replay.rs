@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::InputMap;
+use ld55_summoning::balance::Balance;
+use ld55_summoning::gameplay::*;
+use ld55_summoning::replay::*;
+use ld55_summoning::rng::SeedConfig;
+use ld55_summoning::GameState;
+
+mod common;
+use common::app_in_state;
+
+const MAX_FRAMES: usize = 60 * 60;
+
+fn headless_app() -> App {
+    app_in_state(GameState::AssetLoading, ReplayPlugin)
+}
+
+// walks towards the enemy, throwing a pair of bombs every half second
+fn scripted_input(app: &mut App, frame: usize) {
+    let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+    keys.press(KeyCode::KeyD);
+    if frame.is_multiple_of(30) {
+        keys.press(KeyCode::Space);
+    } else {
+        keys.release(KeyCode::Space);
+    }
+}
+
+// goes through the menus like the real game does
+fn play_match(app: &mut App) -> usize {
+    for state in [GameState::MainMenu, GameState::InGame] {
+        app.update();
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+    }
+
+    for frame in 0..MAX_FRAMES {
+        scripted_input(app, frame);
+        app.update();
+        if *app.world.resource::<State<GameState>>().get() == GameState::GameOver {
+            return frame;
+        }
+    }
+    panic!("match did not finish within {} frames", MAX_FRAMES);
+}
+
+#[test]
+fn finished_match_is_recorded() {
+    let mut app = headless_app();
+    app.insert_resource(SeedConfig(Some(7)));
+    play_match(&mut app);
+
+    let replay = &app.world.resource::<LastReplay>().0;
+    assert_eq!(replay.version, REPLAY_VERSION);
+    assert_eq!(replay.seed, 7);
+    assert_eq!(replay.result, app.world.resource::<GameStatus>().result);
+
+    assert_eq!(replay.tick_rate, DEFAULT_TICK_RATE);
+    assert!(replay.inputs.iter().any(|run| run.spawn_minions));
+    assert!(replay.inputs.len() < replay.ticks());
+
+    let reloaded = Replay::from_ron(&replay.to_ron().unwrap()).unwrap();
+    assert_eq!(&reloaded, replay);
+}
+
+#[test]
+fn playback_reaches_the_recorded_result() {
+    let mut recording = headless_app();
+    let recorded_frames = play_match(&mut recording);
+    let replay = recording.world.resource::<LastReplay>().0.clone();
+
+    // live input is ignored during playback, so the same keys are still being pressed here
+    let mut playback = headless_app();
+    playback
+        .insert_resource(ReplayPlayback::new(replay.clone()))
+        .insert_resource(SeedConfig(Some(99)))
+        .insert_resource(GameMode::Endless);
+    let played_frames = play_match(&mut playback);
+
+    assert_eq!(played_frames, recorded_frames);
+    assert_eq!(playback.world.resource::<GameStatus>().result, replay.result);
+    assert!(!playback.world.contains_resource::<ReplayPlayback>());
+    assert!(!playback.world.contains_resource::<LastReplay>());
+
+    // the launch options apply again to the next match
+    playback.update();
+    assert_eq!(playback.world.resource::<SeedConfig>().0, Some(99));
+    assert_eq!(*playback.world.resource::<GameMode>(), GameMode::Endless);
+}
+
+#[test]
+fn replay_of_another_balance_is_not_played_back() {
+    let mut recording = headless_app();
+    play_match(&mut recording);
+    let mut replay = recording.world.resource::<LastReplay>().0.clone();
+    assert_eq!(replay.balance, Balance::bundled().fingerprint());
+    replay.balance ^= 1;
+
+    let mut playback = headless_app();
+    playback.insert_resource(ReplayPlayback::new(replay));
+    playback.update();
+    playback.world.resource_mut::<NextState<GameState>>().set(GameState::MainMenu);
+    playback.update();
+    assert!(!playback.world.contains_resource::<ReplayPlayback>());
+}
+
+#[test]
+fn quitting_a_replay_hands_the_next_match_back_to_the_player() {
+    let mut recording = headless_app();
+    play_match(&mut recording);
+    let replay = recording.world.resource::<LastReplay>().0.clone();
+
+    let mut playback = headless_app();
+    playback
+        .insert_resource(ReplayPlayback::new(replay))
+        .insert_resource(SeedConfig(Some(99)));
+    for state in [GameState::MainMenu, GameState::InGame] {
+        playback.update();
+        playback.world.resource_mut::<NextState<GameState>>().set(state);
+    }
+    for _ in 0..10 {
+        playback.update();
+    }
+
+    // quit to the main menu from the pause menu, then start a match of one's own
+    playback.world.resource_mut::<NextState<GameState>>().set(GameState::MainMenu);
+    playback.update();
+    assert!(!playback.world.contains_resource::<ReplayPlayback>());
+    assert_eq!(playback.world.resource::<SeedConfig>().0, Some(99));
+    assert_eq!(*playback.world.resource::<State<GameState>>().get(), GameState::MainMenu);
+
+    playback.world.resource_mut::<NextState<GameState>>().set(GameState::InGame);
+    playback.update();
+    let world = &mut playback.world;
+    let bindings = world.query_filtered::<&InputMap<PlayerAction>, With<Player>>().iter(world).count();
+    assert_eq!(bindings, 1);
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_kira_audio::AudioInstance;
+use ld55_summoning::settings::{Settings, SettingsPlugin, SettingsStore};
+
+mod common;
+use common::temp_path;
+
+fn changed_settings() -> Settings {
+    Settings {
+        music_volume: 0.25,
+        effects_volume: 0.75,
+        fullscreen: true,
+        vsync: true,
+        screen_shake: 0.0,
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn settings_survive_a_round_trip() {
+    let settings = changed_settings();
+    let text = settings.to_ron().unwrap();
+    assert_eq!(Settings::from_ron(&text).unwrap(), settings);
+}
+
+#[test]
+fn missing_settings_keep_their_defaults() {
+    let settings = Settings::from_ron("(music_volume: 0.5)").unwrap();
+    assert_eq!(settings.music_volume, 0.5);
+    assert_eq!(settings.effects_volume, Settings::default().effects_volume);
+    assert_eq!(settings.screen_shake, Settings::default().screen_shake);
+}
+
+#[test]
+fn store_falls_back_to_defaults() {
+    let path = temp_path("settings-fallback");
+    let store = SettingsStore {
+        path: Some(path.clone()),
+    };
+    assert_eq!(store.load(), Settings::default());
+
+    std::fs::write(&path, "not settings").unwrap();
+    assert_eq!(store.load(), Settings::default());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn saved_settings_are_applied_at_startup() {
+    let path = temp_path("settings-startup");
+    let store = SettingsStore {
+        path: Some(path.clone()),
+    };
+    store.save(&changed_settings());
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SettingsPlugin))
+        .init_resource::<Assets<AudioInstance>>()
+        .insert_resource(store);
+    app.update();
+
+    assert_eq!(*app.world.resource::<Settings>(), changed_settings());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::Position;
+use ld55_summoning::balance::Balance;
+use ld55_summoning::gameplay::*;
+use ld55_summoning::stats::*;
+
+mod common;
+use common::game_app;
+
+#[test]
+fn chained_explosions_multiply_the_score() {
+    let mut stats = MatchStats::default();
+    stats.record_bomb_hit();
+    stats.record_damage(10);
+    assert_eq!(stats.score, 10);
+
+    stats.tick(1.0);
+    stats.record_bomb_hit();
+    stats.record_kill();
+    assert_eq!(stats.combo, 2);
+    assert_eq!(stats.score, 10 + (KILL_SCORE as f32 * 1.25).round() as u32);
+
+    // the combo runs out when no explosion follows in time
+    stats.tick(COMBO_WINDOW);
+    assert_eq!(stats.combo, 0);
+    assert_eq!(stats.best_combo, 2);
+    assert_eq!(stats.combo_multiplier(), 1.0);
+    assert_eq!(stats.time_survived, 1.0 + COMBO_WINDOW);
+}
+
+#[test]
+fn durations_read_as_minutes_and_seconds() {
+    assert_eq!(format_duration(0.0), "0:00");
+    assert_eq!(format_duration(83.7), "1:23");
+}
+
+#[test]
+fn match_stats_are_tracked() {
+    let mut app = game_app(());
+    app.update();
+
+    // one bomb from the player, one right next to the enemy
+    app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    app.update();
+    app.world.resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::Space);
+    let world = &mut app.world;
+    let enemy_position = world.query_filtered::<&Position, With<Enemy>>().single(world).0;
+    world.spawn((
+        Minion,
+        Name::new("Minion"),
+        Position(enemy_position),
+        Fuse {
+            timer: Timer::from_seconds(0.1, TimerMode::Once),
+        },
+    ));
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let damage = Balance::bundled().minion.damage as u32;
+    let stats = &app.world.resource::<GameStatus>().stats;
+    assert_eq!(stats.bombs_spawned, 2);
+    assert_eq!(stats.bombs_hit, 1);
+    assert_eq!(stats.damage_dealt, damage);
+    assert_eq!(stats.score, damage);
+    assert!(stats.time_survived > 0.4, "{}", stats.time_survived);
+}